@@ -1,4 +1,6 @@
-use discord_interaction::{run, ApplicationCommand, InteractionHandler, Message, Response};
+use discord_interaction::{
+    run, ApplicationCommand, HandlerError, InteractionHandler, Message, Response,
+};
 use lambda_http::Error;
 
 const APPLICATION_PUBLIC_KEY: &str = env!("DEMO_PUBLIC_KEY");
@@ -8,11 +10,12 @@ async fn main() -> Result<(), Error> {
     run::<DemoHandler>(APPLICATION_PUBLIC_KEY).await
 }
 
+#[derive(Default)]
 struct DemoHandler;
 
 impl InteractionHandler for DemoHandler {
-    fn handle_application_command(ac: ApplicationCommand) -> Response {
+    fn handle_application_command(&self, ac: ApplicationCommand) -> Result<Response, HandlerError> {
         let text_content = format!("Hello <@{}>!", ac.user_id);
-        Response::Message(Message::new().text(&text_content))
+        Ok(Response::Message(Message::new().text(&text_content)))
     }
 }
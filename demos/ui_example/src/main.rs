@@ -1,6 +1,6 @@
 use discord_interaction::{
-    run, ApplicationCommand, InteractionHandler, Message, MessageComponent, Modal, ModalSubmit,
-    Response,
+    ApplicationCommand, HandlerError, InteractionHandler, Message, MessageComponent, Modal,
+    ModalSubmit, Response, Runner,
 };
 use lambda_http::Error;
 use regex::Regex;
@@ -9,89 +9,125 @@ const APPLICATION_PUBLIC_KEY: &str = env!("DEMO_PUBLIC_KEY");
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    run::<DemoHandler>(APPLICATION_PUBLIC_KEY).await
+    Runner::<DemoHandler>::new(APPLICATION_PUBLIC_KEY)
+        .on_unknown(|_| {
+            Response::Message(
+                Message::new()
+                    .text("Sorry, I don't know how to handle that.")
+                    .ephemeral(),
+            )
+        })
+        .run()
+        .await
 }
 
+#[derive(Default)]
 struct DemoHandler;
 
 impl InteractionHandler for DemoHandler {
-    fn handle_application_command(ac: ApplicationCommand) -> Response {
+    fn handle_application_command(&self, ac: ApplicationCommand) -> Result<Response, HandlerError> {
         match ac.command_name.as_str() {
-            "hello" => Response::Message(
+            "hello" => Ok(Response::Message(
                 Message::new()
                     .text(&format!("Hello <@{}>!", ac.user_id))
                     .button("the_button", "the button")
                     .button("modal", "input some text")
                     .button("spawn", "spawn new message"),
-            ),
+            )),
 
-            _ => panic!(),
+            _ => Err(HandlerError::unknown()),
         }
     }
 
-    fn handle_message_component(mc: MessageComponent) -> Response {
+    fn handle_message_component(&self, mc: MessageComponent) -> Result<Response, HandlerError> {
         match mc.id.as_str() {
             "the_button" => {
                 let n = get_button_clicks(&mc.source.text).unwrap_or(0);
-                Response::Message(
+                Ok(Response::Message(
                     Message::new()
                         .text(&format!("You've clicked the button {} times.", n + 1))
                         .button("the_button", "the button")
                         .button("modal", "input some text")
                         .button("spawn", "spawn new message")
                         .edit(),
-                )
+                ))
             },
 
-            "spawn" => Response::Message(
+            "spawn" => Ok(Response::Message(
                 Message::new()
                     .text("This is a new message. The message is also *ephemeral*, meaning it's only visible to you.")
                     .ephemeral(),
-            ),
+            )),
 
-            "modal" => Response::Modal(
+            "modal" => Ok(Response::Modal(
                 Modal::new()
                     .id("my_modal")
                     .title("Provide input values.")
                     .field("v1", "A value")
                     .field("v2", "Another value"),
-            ),
+            )),
 
-            _ => panic!(),
+            _ => Err(HandlerError::unknown()),
         }
     }
 
-    fn handle_modal_submit(ms: ModalSubmit) -> Response {
+    fn handle_modal_submit(&self, ms: ModalSubmit) -> Result<Response, HandlerError> {
         match ms.id.as_str() {
             "my_modal" => {
-                let v1 = ms.values.get("v1").unwrap();
-                let v2 = ms.values.get("v2").unwrap();
-                let text = format!(
-                    "{}\nYou entered the values `{}` and `{}`.",
-                    ms.source.text, v1, v2
-                );
-
-                Response::Message(
+                let v1: String = ms.field("v1")?;
+                let v2: String = ms.field("v2")?;
+                let source = ms.source.as_ref().map_or("", |source| source.text.as_str());
+                let text = format!("{}\nYou entered the values `{}` and `{}`.", source, v1, v2);
+
+                Ok(Response::Message(
                     Message::new()
                         .text(&text)
                         .button("the_button", "the button")
                         .button("modal", "input some text")
                         .button("spawn", "spawn new message")
                         .edit(),
-                )
+                ))
             }
 
-            _ => panic!(),
+            _ => Err(HandlerError::unknown()),
         }
     }
 }
 
 fn get_button_clicks(msg: &str) -> Option<u64> {
     let pattern = "You've clicked the button [0-9]* times.";
-    let re = Regex::new(&pattern).unwrap();
+    let re = Regex::new(pattern).unwrap();
     let mut range = re.find(msg)?.range();
     range.start += 26;
     range.end -= 7;
     let n = msg[range].parse::<u64>().ok()?;
     Some(n)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Any valid public key will do, since the runner doesn't verify requests in insecure local
+    /// mode.
+    const TEST_PUBLIC_KEY: &str =
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+    #[tokio::test]
+    async fn my_modal_submit() {
+        let body = include_str!("../../../tests/fixtures/modal_submit.json");
+        let headers: [(&str, &str); 0] = [];
+        let res = Runner::<DemoHandler>::new(TEST_PUBLIC_KEY)
+            .insecure_local_mode()
+            .handle_http(&headers[..], body.as_bytes())
+            .await;
+        assert_eq!(res.status, 200);
+
+        let res: serde_json::Value = serde_json::from_slice(&res.body).unwrap();
+        assert_eq!(res["type"], 7);
+        assert_eq!(
+            res["data"]["content"],
+            "Hello <@53908232506183680>!\nYou entered the values `first` and `second`."
+        );
+    }
+}
@@ -0,0 +1,520 @@
+//! Procedural macros for `discord_interaction`. These are re-exported by `discord_interaction` when
+//! its `derive` feature is enabled, and aren't meant to be depended on directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Field, Fields, FnArg, ItemFn,
+    Lit, LitStr, Meta, Pat, PathArguments, Type,
+};
+
+/// Implements `FromModal` for a struct with named fields, populating each field from the modal
+/// input whose id matches the field's name. A different id can be given with
+/// `#[modal(id = "...")]`.
+///
+/// `Option` fields are `None` if their input was left empty, `Vec` fields are read from select
+/// menus, and any other field is parsed from its input's text with `FromStr`.
+#[proc_macro_derive(FromModal, attributes(modal))]
+pub fn derive_from_modal(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_modal(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand_from_modal(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let inits = named_fields(&input, "FromModal")?
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let id = input_id(field)?.unwrap_or_else(|| ident.to_string());
+
+            let read = match outer_type(&field.ty).as_deref() {
+                Some("Option") => quote!(optional_field),
+                Some("Vec") => quote!(selection),
+                _ => quote!(field),
+            };
+
+            Ok(quote!(#ident: ms.#read(#id)?))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::discord_interaction::FromModal for #name #ty_generics #where_clause {
+            fn from_modal(
+                ms: &::discord_interaction::ModalSubmit,
+            ) -> ::std::result::Result<Self, ::discord_interaction::ModalError> {
+                ::std::result::Result::Ok(#name {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+/// Implements `FromOptions` for a struct with named fields, populating each field from the
+/// command option whose name matches the field's name. A different name can be given with
+/// `#[option(name = "...")]`.
+///
+/// Fields are read with `FromOptionValue`, and `Option` fields are `None` if their option wasn't
+/// given. Missing options and options of the wrong type are reported as errors.
+#[proc_macro_derive(FromOptions, attributes(option))]
+pub fn derive_from_options(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_options(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand_from_options(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let inits = named_fields(&input, "FromOptions")?
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let option = option_name(field)?.unwrap_or_else(|| ident.to_string());
+
+            Ok(match option_inner_type(&field.ty) {
+                Some(inner) => quote!(#ident: ac.option_value::<#inner>(#option)?),
+                None => {
+                    let ty = &field.ty;
+                    quote!(#ident: ac.required_option::<#ty>(#option)?)
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::discord_interaction::extract::FromOptions
+            for #name #ty_generics #where_clause
+        {
+            fn from_options(
+                ac: &::discord_interaction::ApplicationCommand,
+            ) -> ::std::result::Result<Self, ::discord_interaction::HandlerError> {
+                ::std::result::Result::Ok(#name {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+/// The named fields of a struct, for deriving the given trait.
+fn named_fields<'a>(
+    input: &'a DeriveInput,
+    trait_name: &str,
+) -> syn::Result<&'a Punctuated<Field, Comma>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                format!(
+                    "{} can only be derived for structs with named fields",
+                    trait_name
+                ),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} can only be derived for structs", trait_name),
+        )),
+    }
+}
+
+/// Reads the name given with `#[option(name = "...")]`, if any.
+fn option_name(field: &Field) -> syn::Result<Option<String>> {
+    let mut name = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("option"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `name`"))
+            }
+        })?;
+    }
+
+    Ok(name)
+}
+
+/// Reads the id given with `#[modal(id = "...")]`, if any.
+fn input_id(field: &Field) -> syn::Result<Option<String>> {
+    let mut id = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("modal"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                id = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `id`"))
+            }
+        })?;
+    }
+
+    Ok(id)
+}
+
+/// The name of a field's outermost type, e.g. `Option` for `Option<u32>`.
+fn outer_type(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Turns a function into a slash command, generating both its handler and its definition. The
+/// function's arguments are the command's options, named after the arguments, and typed by
+/// `FromOptionValue`. `Option` arguments are optional options. The function can be `async`
+/// (except on wasm32, where handlers can't block), in which case it's blocked on, which requires
+/// a multi-threaded Tokio runtime; on any other runtime, the command fails with a `HandlerError`.
+/// The function should return `Result<Response, HandlerError>`.
+///
+/// The command is named after the function, and described by its doc comment. The name can be
+/// changed with `#[slash_command(name = "...")]`, and each option's name and description with
+/// `#[option(name = "...", description = "...")]` on its argument.
+///
+/// The macro replaces the function with a unit struct of the same name, which implements
+/// `SlashCommand`, for registering with `Router::slash_command`.
+#[proc_macro_attribute]
+pub fn slash_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut name = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("expected `name`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+
+    let item = parse_macro_input!(item as ItemFn);
+    expand_slash_command(name, item)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand_slash_command(name: Option<String>, mut item: ItemFn) -> syn::Result<TokenStream2> {
+    let ident = item.sig.ident.clone();
+    let vis = item.vis.clone();
+    let name = name.unwrap_or_else(|| ident.to_string());
+
+    if !item.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.sig.generics,
+            "slash commands can't be generic",
+        ));
+    }
+
+    let description = doc_comment(&item.attrs);
+    if description.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.sig.ident,
+            "slash commands need a doc comment, which is used as their description",
+        ));
+    }
+
+    let mut options = Vec::new();
+    let mut reads = Vec::new();
+    let mut args = Vec::new();
+
+    for (i, input) in item.sig.inputs.iter_mut().enumerate() {
+        let arg = match input {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "slash commands can't take `self`",
+                ))
+            }
+        };
+
+        let mut option_name = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.to_string(),
+            pat => {
+                return Err(syn::Error::new_spanned(
+                    pat,
+                    "slash command arguments must be plain identifiers",
+                ))
+            }
+        };
+        let mut option_description = None;
+
+        for attr in arg
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("option"))
+        {
+            attr.parse_nested_meta(|meta: ParseNestedMeta| {
+                if meta.path.is_ident("name") {
+                    option_name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("description") {
+                    option_description = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name` or `description`"))
+                }
+            })?;
+        }
+        arg.attrs.retain(|attr| !attr.path().is_ident("option"));
+
+        let option_description = option_description.unwrap_or_else(|| option_name.clone());
+        let (ty, required, read) = match option_inner_type(&arg.ty) {
+            Some(inner) => (inner, false, quote!(option_value)),
+            None => (&*arg.ty, true, quote!(required_option)),
+        };
+
+        options.push(quote! {
+            ::discord_interaction::OptionDefinition {
+                kind: <#ty as ::discord_interaction::FromOptionValue>::TYPE,
+                name: #option_name.to_string(),
+                description: #option_description.to_string(),
+                required: #required,
+            }
+        });
+
+        let var = format_ident!("__arg{}", i);
+        reads.push(quote!(let #var = __ac.#read::<#ty>(#option_name)?;));
+        args.push(var);
+    }
+
+    let call = if item.sig.asyncness.is_some() {
+        quote!(::discord_interaction::block_on(__handler(#(#args),*))?)
+    } else {
+        quote!(__handler(#(#args),*))
+    };
+
+    item.attrs.retain(|attr| !attr.path().is_ident("doc"));
+    item.sig.ident = format_ident!("__handler");
+    item.vis = syn::Visibility::Inherited;
+
+    Ok(quote! {
+        #[doc = #description]
+        #[allow(non_camel_case_types)]
+        #vis struct #ident;
+
+        impl ::discord_interaction::SlashCommand for #ident {
+            fn definition(&self) -> ::discord_interaction::CommandDefinition {
+                ::discord_interaction::CommandDefinition {
+                    name: #name.to_string(),
+                    description: #description.to_string(),
+                    options: ::std::vec![#(#options),*],
+                }
+            }
+
+            fn handle(
+                &self,
+                __ac: ::discord_interaction::ApplicationCommand,
+            ) -> ::std::result::Result<
+                ::discord_interaction::Response,
+                ::discord_interaction::HandlerError,
+            > {
+                #item
+                #(#reads)*
+                #call
+            }
+        }
+    })
+}
+
+/// Joins the lines of an item's doc comment into a single line.
+fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(doc), ..
+                }) => Some(doc.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The `T` of an `Option<T>` type, if the type is an `Option`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn from_options_reads_each_field() {
+        let input: DeriveInput = parse_quote! {
+            struct Greet {
+                user: UserId,
+                #[option(name = "count")]
+                times: Option<i64>,
+            }
+        };
+
+        let expected = quote! {
+            impl ::discord_interaction::extract::FromOptions for Greet {
+                fn from_options(
+                    ac: &::discord_interaction::ApplicationCommand,
+                ) -> ::std::result::Result<Self, ::discord_interaction::HandlerError> {
+                    ::std::result::Result::Ok(Greet {
+                        user: ac.required_option::<UserId>("user")?,
+                        times: ac.option_value::<i64>("count")?,
+                    })
+                }
+            }
+        };
+        assert_eq!(
+            expand_from_options(input).unwrap().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    fn from_options_needs_named_fields() {
+        let input: DeriveInput = parse_quote!(
+            struct Greet(i64);
+        );
+        let err = expand_from_options(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "FromOptions can only be derived for structs with named fields"
+        );
+
+        let input: DeriveInput = parse_quote!(
+            enum Greet {
+                Hi,
+            }
+        );
+        let err = expand_from_options(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "FromOptions can only be derived for structs"
+        );
+
+        let input: DeriveInput = parse_quote! {
+            struct Greet {
+                #[option(id = "count")]
+                times: i64,
+            }
+        };
+        let err = expand_from_options(input).unwrap_err();
+        assert_eq!(err.to_string(), "expected `name`");
+    }
+
+    #[test]
+    fn from_modal_picks_the_reader_by_type() {
+        let input: DeriveInput = parse_quote! {
+            struct Feedback {
+                #[modal(id = "v1")]
+                rating: u8,
+                comment: Option<String>,
+                topics: Vec<String>,
+            }
+        };
+
+        let output = expand_from_modal(input).unwrap().to_string();
+        assert!(output.contains(&quote!(rating: ms.field("v1")?).to_string()));
+        assert!(output.contains(&quote!(comment: ms.optional_field("comment")?).to_string()));
+        assert!(output.contains(&quote!(topics: ms.selection("topics")?).to_string()));
+    }
+
+    #[test]
+    fn slash_command_definition() {
+        let item: ItemFn = parse_quote! {
+            /// Greets a user,
+            /// a number of times.
+            fn greet(
+                #[option(description = "Who to greet")] user: Snowflake,
+                times: Option<i64>,
+            ) -> Result<Response, HandlerError> {
+                todo!()
+            }
+        };
+
+        let output = expand_slash_command(Some("hello".to_string()), item)
+            .unwrap()
+            .to_string();
+        assert!(output.contains(&quote!(name: "hello".to_string()).to_string()));
+        assert!(output.contains(
+            &quote!(description: "Greets a user, a number of times.".to_string()).to_string()
+        ));
+        assert!(output.contains(&quote!(description: "Who to greet".to_string()).to_string()));
+        assert!(output.contains(&quote!(required: false).to_string()));
+        assert!(
+            output.contains(&quote!(let __arg1 = __ac.option_value::<i64>("times")?;).to_string())
+        );
+        assert!(!output.contains("block_on"));
+    }
+
+    #[test]
+    fn async_slash_commands_are_blocked_on() {
+        let item: ItemFn = parse_quote! {
+            /// Pings.
+            async fn ping() -> Result<Response, HandlerError> {
+                todo!()
+            }
+        };
+
+        let output = expand_slash_command(None, item).unwrap().to_string();
+        assert!(output.contains(&quote!(::discord_interaction::block_on(__handler())?).to_string()));
+    }
+
+    #[test]
+    fn slash_commands_need_a_description() {
+        let item: ItemFn = parse_quote! {
+            fn ping() -> Result<Response, HandlerError> {
+                todo!()
+            }
+        };
+
+        let err = expand_slash_command(None, item).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "slash commands need a doc comment, which is used as their description"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+/*!
+ * Serving interactions from an [actix-web](https://docs.rs/actix-web) app, for self-hosted bots.
+ * The `Interaction` extractor takes an interaction request, and `Interaction::dispatch` verifies
+ * and handles it with the runner in the app data, giving a responder:
+ *
+ * ```ignore
+ * async fn interactions(
+ *     interaction: Interaction,
+ *     runner: web::Data<Runner<MyHandler>>,
+ * ) -> HttpResponseParts {
+ *     interaction.dispatch(&runner).await
+ * }
+ *
+ * App::new()
+ *     .app_data(web::Data::new(Runner::<MyHandler>::new(&public_key)))
+ *     .route("/interactions", web::post().to(interactions))
+ * ```
+ *
+ * Requires the `actix` feature.
+ */
+
+use super::auth::{Headers, HttpResponseParts, Runner};
+use super::handler::InteractionHandler;
+use actix_web::body::BoxBody;
+use actix_web::dev::Payload;
+use actix_web::http::header::{HeaderMap, CONTENT_TYPE};
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{Error, FromRequest, HttpRequest, HttpResponse, Responder};
+use std::future::Future;
+use std::pin::Pin;
+
+/// An interaction request, as its headers and body. Its signature is verified when it's
+/// dispatched, like everything else about the request, with the runner's configuration.
+pub struct Interaction {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Interaction {
+    /// Verifies and handles the interaction with the given runner, returning the response to send
+    /// back.
+    pub async fn dispatch<T>(self, runner: &Runner<T>) -> HttpResponseParts
+    where
+        T: InteractionHandler + Send + Sync + 'static,
+    {
+        runner.handle_http(&self.headers, &self.body).await
+    }
+}
+
+impl FromRequest for Interaction {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let headers = req.headers().clone();
+        let body = Bytes::from_request(req, payload);
+
+        Box::pin(async move {
+            Ok(Interaction {
+                headers,
+                body: body.await?,
+            })
+        })
+    }
+}
+
+impl Responder for HttpResponseParts {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        HttpResponse::build(status)
+            .insert_header((CONTENT_TYPE, self.content_type))
+            .body(self.body)
+    }
+}
+
+impl Headers for HeaderMap {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.get(name)?.to_str().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ApplicationCommand, HandlerError, Message, Response};
+    use actix_web::test::TestRequest;
+    use serde_json::json;
+
+    struct TestHandler;
+
+    impl InteractionHandler for TestHandler {
+        fn handle_application_command(
+            &self,
+            _ac: ApplicationCommand,
+        ) -> Result<Response, HandlerError> {
+            Ok(Response::Message(Message::new().text("Hello!")))
+        }
+    }
+
+    #[test]
+    fn runner_configuration_applies() {
+        let body = json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 2,
+            "user": { "id": "53908232506183680", "username": "mason" },
+            "data": { "id": "771825006014889984", "name": "hello", "type": 1 },
+        });
+        let runner = Runner::with_handler(
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            TestHandler,
+        )
+        .insecure_local_mode();
+        let (req, mut payload) = TestRequest::post()
+            .set_payload(body.to_string())
+            .to_http_parts();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let res = runtime.block_on(async {
+            let interaction = Interaction::from_request(&req, &mut payload).await.unwrap();
+            interaction.dispatch(&runner).await
+        });
+        assert_eq!(res.status, 200);
+        assert!(String::from_utf8(res.body).unwrap().contains("Hello!"));
+    }
+}
@@ -1,96 +1,836 @@
-use crate::InteractionHandler;
+use crate::discord_types::{
+    AllowedMentions, FileData, InteractionCallbackType, InteractionRequest, InteractionResponse,
+    InteractionType,
+};
+use crate::handler::process_interaction;
+use crate::{HandlerError, InteractionHandler, InvalidPublicKey, Message, Middleware, State};
+use base64::prelude::{Engine, BASE64_STANDARD};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey, PUBLIC_KEY_LENGTH};
-use lambda_http::http::{HeaderMap, StatusCode};
-use lambda_http::{service_fn, Body, Error, Request, Response};
+use http::StatusCode;
+#[cfg(feature = "lambda")]
+use lambda_http::http::header::CONTENT_TYPE;
+#[cfg(feature = "lambda")]
+use lambda_http::request::RequestContext;
+#[cfg(feature = "lambda")]
+use lambda_http::{service_fn, Body, Error, Request, RequestExt, Response};
 use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Sets up an interaction handler on AWS Lambda to receive and process incoming Discord interactions. This function takes in an application public key, which should match the one provided with your Discord bot.
+///
+/// The function can be invoked through API Gateway (REST or HTTP APIs), a Lambda Function URL, or
+/// an Application Load Balancer; each event shape is converted to the same HTTP request. Bodies
+/// that the event marks as base64-encoded (`isBase64Encoded`), as Function URLs and load
+/// balancers do for some content types, are decoded before the signature is checked, so it's
+/// always checked against the exact bytes Discord sent. For integrations that pass the body on
+/// still encoded without marking it, see `Runner::base64_body`, and to accept only one API
+/// Gateway payload format, see `Runner::payload_format`.
+///
+/// The public key is decoded once, at startup, and this returns an `InvalidPublicKey` error if it
+/// isn't valid.
+#[cfg(feature = "lambda")]
 pub async fn run<T>(app_pk: &str) -> Result<(), Error>
 where
-    T: InteractionHandler + Sync,
+    T: InteractionHandler + Default + Send + Sync + 'static,
 {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        // disable printing the name of the module in every log line.
-        .with_target(false)
-        // disabling time is handy because CloudWatch will add the ingestion time.
-        .without_time()
-        .init();
+    Runner::<T>::try_new(app_pk)?.run().await
+}
+
+/// Sets up the given interaction handler on AWS Lambda, as with `run`, for handlers that can't
+/// be created with `Default`, such as a `Router`.
+#[cfg(feature = "lambda")]
+pub async fn run_handler<T>(app_pk: &str, handler: T) -> Result<(), Error>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    Runner::try_with_handler(app_pk, handler)?.run().await
+}
+
+/// Sets up an interaction handler on AWS Lambda, as with `run`, sharing the given application
+/// state with the handler. See `State`.
+#[cfg(feature = "lambda")]
+pub async fn run_with_state<T, S>(app_pk: &str, state: S) -> Result<(), Error>
+where
+    T: InteractionHandler + Default + Send + Sync + 'static,
+    S: std::any::Any + Send + Sync,
+{
+    Runner::<T>::try_new(app_pk)?.state(state).run().await
+}
+
+/// Processes a single incoming request, returning the HTTP response that would be sent back to Discord. Unlike `run`, this does not enter the AWS Lambda runtime loop, so it can be used to feed signed, synthetic requests through verification and dispatch, and inspect the result.
+#[cfg(feature = "lambda")]
+pub async fn handle_one<T>(app_pk: &str, req: Request) -> Result<Response<Body>, Error>
+where
+    T: InteractionHandler + Default + Send + Sync + 'static,
+{
+    Runner::<T>::try_new(app_pk)?.handle_one(req).await
+}
+
+/// Verifies, parses and dispatches a single interaction to `handler`, given the headers and body
+/// of the HTTP request that Discord sent, and returns the HTTP response to send back. This doesn't
+/// depend on AWS Lambda or any async runtime, so it can be used to serve interactions from any web
+/// framework. The handler is run with the same defaults as `run`, but without a `Runner`'s
+/// configuration, such as middleware.
+///
+/// The public key is decoded for every request, and a key that isn't valid is answered with a 500
+/// Internal Server Error. To decode it only once, use `handle_interaction_with_key`.
+pub fn handle_interaction<T, H>(
+    public_key: &str,
+    headers: &H,
+    body: &[u8],
+    handler: &T,
+) -> HttpResponseParts
+where
+    T: InteractionHandler,
+    H: Headers + ?Sized,
+{
+    match PublicKey::parse(public_key) {
+        Ok(public_key) => handle_interaction_with_key(&public_key, headers, body, handler),
+        Err(err) => {
+            tracing::error!({ %err }, "Can't verify requests");
+            HttpResponseParts::error(StatusCode::INTERNAL_SERVER_ERROR.as_u16())
+        }
+    }
+}
+
+/// Verifies, parses and dispatches a single interaction, as with `handle_interaction`, with an
+/// application public key that has already been decoded.
+pub fn handle_interaction_with_key<T, H>(
+    public_key: &PublicKey,
+    headers: &H,
+    body: &[u8],
+    handler: &T,
+) -> HttpResponseParts
+where
+    T: InteractionHandler,
+    H: Headers + ?Sized,
+{
+    let res = verify(body, headers, &public_key.0)
+        .and_then(|req_json| parse(req_json, None))
+        .and_then(|interaction| {
+            let on_error = error_handler(DEFAULT_FALLBACK_MESSAGE.to_string(), None, None::<fn()>);
+            process_interaction(handler, &interaction, &[], &on_error, &State::default())
+                .map_err(infrastructure_failure)
+        })
+        .map(|res| encode(without_followup(res), true));
+
+    HttpResponseParts::from_result(res)
+}
+
+/// A decoded application public key, for verifying requests with `handle_interaction_with_key`.
+#[derive(Clone, Copy, Debug)]
+pub struct PublicKey(VerifyingKey);
+
+impl PublicKey {
+    /// Decodes a hex-encoded application public key, failing if it isn't a valid Ed25519 public
+    /// key.
+    pub fn parse(app_pk: &str) -> Result<Self, InvalidPublicKey> {
+        parse_public_key(app_pk).map(PublicKey)
+    }
+}
+
+/// The headers of an HTTP request, for `handle_interaction`. Header names are matched
+/// case-insensitively.
+pub trait Headers {
+    /// Gets the value of the header with the given name, if it's present and valid text.
+    fn header(&self, name: &str) -> Option<&str>;
+}
+
+#[cfg(any(feature = "lambda", feature = "warp"))]
+impl Headers for http02::HeaderMap {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.get(name)?.to_str().ok()
+    }
+}
+
+impl Headers for http::HeaderMap {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.get(name)?.to_str().ok()
+    }
+}
+
+impl Headers for HashMap<String, String> {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl<K, V> Headers for [(K, V)]
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    fn header(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(key, _)| key.as_ref().eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref())
+    }
+}
+
+/// The payload format of the API Gateway events that invoke a Lambda function, for
+/// `Runner::payload_format`.
+///
+/// The formats differ in ways that could affect signature verification, which the runner accounts
+/// for either way: header names are matched case-insensitively, and bodies that the event marks as
+/// base64-encoded are decoded before they're verified.
+#[cfg(feature = "lambda")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PayloadFormat {
+    /// Version 1.0, sent by REST APIs, and by HTTP APIs configured to use it. Header names keep the
+    /// casing they were sent with, and bodies are base64-encoded only if their content type is one
+    /// of the API's binary media types.
+    V1,
+    /// Version 2.0, sent by HTTP APIs and Lambda Function URLs. Header names are lowercased, and
+    /// bodies are base64-encoded unless their content type is text, e.g. `application/json`.
+    V2,
+}
+
+/// An HTTP response to an interaction request, independent of any web framework.
+pub struct HttpResponseParts {
+    /// The status code, e.g. 200.
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponseParts {
+    pub(crate) fn from_result(res: Result<(String, Vec<u8>), StatusCode>) -> Self {
+        match res {
+            Ok((content_type, body)) => HttpResponseParts {
+                status: StatusCode::OK.as_u16(),
+                content_type,
+                body,
+            },
+
+            Err(code) => HttpResponseParts::error(code.as_u16()),
+        }
+    }
+
+    /// The response to a request whose interaction was responded to through Discord's API
+    /// instead.
+    #[cfg(feature = "download")]
+    pub(crate) fn accepted() -> Self {
+        HttpResponseParts {
+            status: StatusCode::ACCEPTED.as_u16(),
+            content_type: "text/plain".to_string(),
+            body: Vec::new(),
+        }
+    }
+
+    /// The response to a request that couldn't be handled, with the given status code.
+    pub(crate) fn error(status: u16) -> Self {
+        HttpResponseParts {
+            status,
+            content_type: "text/plain".to_string(),
+            body: b"Error when handling request.".to_vec(),
+        }
+    }
+}
+
+const DEFAULT_FALLBACK_MESSAGE: &str = "Something went wrong.";
+
+type UnknownFallback = dyn Fn(&InteractionRequest) -> crate::Response + Send + Sync;
+type InteractionHook = dyn Fn(&InteractionRequest) -> bool + Send + Sync;
+type UnhandledHook = dyn Fn(&str) + Send + Sync;
+
+/// A configurable interaction runner. `run` is shorthand for a runner with the default
+/// configuration; build one of these directly to customize how interactions are dispatched to the
+/// handler `T`, e.g. by adding middleware.
+pub struct Runner<T> {
+    verifying_key: VerifyingKey,
+    middleware: Vec<Arc<dyn Middleware + Send + Sync>>,
+    guard: Option<Duration>,
+    fallback_message: String,
+    on_unknown: Option<Arc<UnknownFallback>>,
+    on_interaction: Option<Box<InteractionHook>>,
+    on_unhandled: Option<Arc<UnhandledHook>>,
+    state: State,
+    safe_mentions: bool,
+    base64_body: bool,
+    insecure_local_mode: bool,
+    #[cfg(feature = "lambda")]
+    payload_format: Option<PayloadFormat>,
+    handler: Arc<T>,
+}
 
-    lambda_http::run(service_fn(|req: Request| async {
-        handle_request::<T>(req, app_pk).await
-    }))
-    .await
+impl<T> Runner<T>
+where
+    T: InteractionHandler + Default + Send + Sync + 'static,
+{
+    /// Creates a new runner, using the given application public key to verify incoming requests,
+    /// and the handler's default value to handle them. Panics if the key isn't valid.
+    pub fn new(app_pk: &str) -> Self {
+        Runner::with_handler(app_pk, T::default())
+    }
+
+    /// Creates a new runner, as with `new`, failing if the key isn't valid.
+    pub fn try_new(app_pk: &str) -> Result<Self, InvalidPublicKey> {
+        Runner::try_with_handler(app_pk, T::default())
+    }
 }
 
-async fn handle_request<T>(req: Request, app_pk: &str) -> Result<Response<Body>, Error>
+impl<T> Runner<T>
 where
-    T: InteractionHandler + Sync,
+    T: InteractionHandler + Send + Sync + 'static,
 {
-    let req_body = std::str::from_utf8(req.body()).unwrap();
-    let headers = req.headers();
+    /// Creates a new runner, using the given application public key to verify incoming requests,
+    /// and the given handler to handle them. The key is decoded once, here, rather than for each
+    /// request, so this panics if it isn't a valid hex-encoded Ed25519 public key.
+    pub fn with_handler(app_pk: &str, handler: T) -> Self {
+        Runner::try_with_handler(app_pk, handler).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a new runner, as with `with_handler`, failing if the key isn't valid.
+    pub fn try_with_handler(app_pk: &str, handler: T) -> Result<Self, InvalidPublicKey> {
+        Ok(Runner {
+            verifying_key: parse_public_key(app_pk)?,
+            middleware: Vec::new(),
+            guard: None,
+            fallback_message: DEFAULT_FALLBACK_MESSAGE.to_string(),
+            on_unknown: None,
+            on_interaction: None,
+            on_unhandled: None,
+            state: State::default(),
+            safe_mentions: true,
+            base64_body: false,
+            insecure_local_mode: false,
+            #[cfg(feature = "lambda")]
+            payload_format: None,
+            handler: Arc::new(handler),
+        })
+    }
+
+    /// Adds a middleware to the end of the chain. See `Middleware` for details on ordering.
+    pub fn middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware + Send + Sync + 'static,
+    {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Guards the handling of each interaction: if the handler (or any middleware) panics, or
+    /// doesn't finish within `timeout`, the details are logged and the user is sent an ephemeral
+    /// fallback message instead of seeing Discord's "interaction failed" error. Discord expects a
+    /// response within three seconds, so `timeout` should leave some room for the round trip.
+    pub fn guard(mut self, timeout: Duration) -> Self {
+        self.guard = Some(timeout);
+        self
+    }
+
+    /// Sets the text of the message sent when a handler returns an error without a reply of its
+    /// own, when a handler panics or returns a response that Discord would reject, or when a
+    /// guarded interaction times out (see `guard`).
+    pub fn fallback_message(mut self, text: &str) -> Self {
+        self.fallback_message = text.to_string();
+        self
+    }
+
+    /// Sets the response to interactions that the handler doesn't recognize, i.e. for which it
+    /// returns `HandlerError::unknown()`, e.g. a friendly ephemeral message. Without this, unknown
+    /// interactions are reported like any other handler error.
+    pub fn on_unknown<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn(&InteractionRequest) -> crate::Response + Send + Sync + 'static,
+    {
+        self.on_unknown = Some(Arc::new(fallback));
+        self
+    }
+
+    /// Shares application state, such as database pools or configuration, with the handler. The
+    /// state can be read from each handler payload by its type, e.g. with
+    /// `ac.state.get::<AppState>()`.
+    pub fn state<S>(mut self, state: S) -> Self
+    where
+        S: std::any::Any + Send + Sync,
+    {
+        self.state.set(state);
+        self
+    }
+
+    /// Gives the handler a bot token, for the payload methods that call Discord's HTTP API on the
+    /// bot's behalf, e.g. `ApplicationCommand::fetch_user`. Interactions themselves don't need a
+    /// token, so this is only needed for those methods. Requires the `download` feature.
+    #[cfg(feature = "download")]
+    pub fn bot_token(mut self, token: &str) -> Self {
+        self.state.set_bot_token(token);
+        self
+    }
+
+    /// Sets a hook that's called with every verified interaction before it's dispatched, including
+    /// Discord's pings, which never reach the handler or middleware. This is a place to e.g. warm
+    /// caches or log. Returning false vetoes the interaction, which is then rejected with a 403
+    /// Forbidden status rather than handled.
+    pub fn on_interaction<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&InteractionRequest) -> bool + Send + Sync + 'static,
+    {
+        self.on_interaction = Some(Box::new(hook));
+        self
+    }
+
+    /// Sets a hook that's called with the raw JSON of every request that dispatch falls through
+    /// on: interactions the handler doesn't recognize (i.e. for which it returns
+    /// `HandlerError::unknown()`), and interactions of types this library doesn't support. This is
+    /// a place to alert on drift between the commands registered with Discord and the deployed
+    /// code. The hook only observes; the response is unaffected (see `on_unknown`).
+    pub fn on_unhandled<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.on_unhandled = Some(Arc::new(hook));
+        self
+    }
+
+    /// Restores Discord's default mention behavior for messages that don't set their own allowed
+    /// mentions, under which every mention in the text notifies whoever it mentions. By default,
+    /// the runner instead allows no mentions, so that a message echoing user input can't
+    /// accidentally ping `@everyone`.
+    pub fn discord_default_mentions(mut self) -> Self {
+        self.safe_mentions = false;
+        self
+    }
+
+    /// Decodes request bodies from base64 before verifying them, for integrations that pass the
+    /// body on still encoded without marking it as such, e.g. an API Gateway REST API with binary
+    /// media types enabled and a custom mapping template. The signature is checked against the
+    /// decoded bytes, which are what Discord signed, and bodies that aren't valid base64 are
+    /// rejected with a 400 Bad Request status. Lambda events marked with `isBase64Encoded` are
+    /// already decoded, so they don't need this.
+    pub fn base64_body(mut self) -> Self {
+        self.base64_body = true;
+        self
+    }
+
+    /// **Insecure: for local development only.** Skips verifying request signatures, so that the
+    /// endpoint can be tried out locally, e.g. with `curl`, without signing payloads. Anyone who
+    /// can reach the endpoint can then impersonate Discord, so this must never be enabled in a
+    /// deployment; every request handled this way logs a warning. The runner still needs a valid
+    /// public key.
+    pub fn insecure_local_mode(mut self) -> Self {
+        self.insecure_local_mode = true;
+        self
+    }
+
+    /// Only accepts Lambda events of the given API Gateway payload format, rejecting any others,
+    /// e.g. from a load balancer or a second API, with a 400 Bad Request status. By default, events
+    /// of either format, and from load balancers, are accepted.
+    #[cfg(feature = "lambda")]
+    pub fn payload_format(mut self, format: PayloadFormat) -> Self {
+        self.payload_format = Some(format);
+        self
+    }
+
+    /// Runs the interaction handler on AWS Lambda, as with `run`.
+    #[cfg(feature = "lambda")]
+    pub async fn run(self) -> Result<(), Error> {
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            // disable printing the name of the module in every log line.
+            .with_target(false)
+            // disabling time is handy because CloudWatch will add the ingestion time.
+            .without_time()
+            .init();
+
+        lambda_http::run(service_fn(|req: Request| async {
+            self.handle_one(req).await
+        }))
+        .await
+    }
+
+    /// Processes a single incoming request, as with `handle_one`.
+    #[cfg(feature = "lambda")]
+    pub async fn handle_one(&self, req: Request) -> Result<Response<Body>, Error> {
+        let format = match req.request_context_ref() {
+            Some(RequestContext::ApiGatewayV1(_)) => Some(PayloadFormat::V1),
+            Some(RequestContext::ApiGatewayV2(_)) => Some(PayloadFormat::V2),
+            _ => None,
+        };
+
+        let res = match self.payload_format {
+            Some(expected) if format != Some(expected) => {
+                tracing::warn!(
+                    ?expected,
+                    ?format,
+                    "Rejected event of unexpected payload format"
+                );
+                HttpResponseParts::error(StatusCode::BAD_REQUEST.as_u16())
+            }
+            _ => self.handle_http(req.headers(), req.body()).await,
+        };
+
+        // Multipart bodies may hold binary file data, and are returned base64-encoded, while
+        // everything else is returned as plain text, which every event source accepts.
+        let body: Body = if res.content_type.starts_with("multipart/") {
+            res.body.into()
+        } else {
+            String::from_utf8_lossy(&res.body).into_owned().into()
+        };
+
+        Ok(Response::builder()
+            .status(res.status)
+            .header(CONTENT_TYPE, res.content_type)
+            .body(body)
+            .unwrap())
+    }
+
+    /// Processes a single incoming request, given its headers and body, as with
+    /// `handle_interaction`, but with this runner's configuration. This is the entry point for
+    /// serving interactions from web frameworks other than AWS Lambda.
+    pub async fn handle_http<H>(&self, headers: &H, body: &[u8]) -> HttpResponseParts
+    where
+        H: Headers + ?Sized,
+    {
+        let decoded;
+        let body = if self.base64_body {
+            match BASE64_STANDARD.decode(body) {
+                Ok(bytes) => {
+                    decoded = bytes;
+                    &decoded[..]
+                }
+                Err(_) => return HttpResponseParts::error(StatusCode::BAD_REQUEST.as_u16()),
+            }
+        } else {
+            body
+        };
+
+        let verified = if self.insecure_local_mode {
+            tracing::warn!("INSECURE LOCAL MODE: handling request without verifying its signature");
+            std::str::from_utf8(body).map_err(|_| StatusCode::BAD_REQUEST)
+        } else {
+            verify(body, headers, &self.verifying_key)
+        };
+
+        let res = match verified {
+            Ok(req_json) => self.handle_body(req_json).await,
+            Err(code) => Err(code),
+        };
+
+        res.unwrap_or_else(|code| HttpResponseParts::error(code.as_u16()))
+    }
+
+    /// Handles a verified request body, returning the HTTP response.
+    async fn handle_body(&self, req_json: &str) -> Result<HttpResponseParts, StatusCode> {
+        let interaction = parse(req_json, self.on_unhandled.as_deref())?;
+
+        if let Some(hook) = &self.on_interaction {
+            if !hook(&interaction) {
+                tracing::info!("Interaction vetoed");
+                return Err(StatusCode::FORBIDDEN);
+            }
+        }
+
+        let on_unhandled = self.on_unhandled.clone().map(|hook| {
+            let req_json = req_json.to_string();
+            move || hook(&req_json)
+        });
+        let on_error = error_handler(
+            self.fallback_message.clone(),
+            self.on_unknown.clone(),
+            on_unhandled,
+        );
 
-    match verify(&req_body, headers, app_pk) {
-        Ok(()) => {
-            let res_body = handle_body::<T>(&req_body).unwrap_or("{}".to_string());
+        #[cfg(feature = "download")]
+        let callback = (
+            interaction.id.clone(),
+            interaction.application_id.clone(),
+            interaction.token.clone(),
+        );
 
-            Ok(Response::builder()
-                .status(StatusCode::OK)
-                .header("Content-Type", "application/json")
-                .body(res_body.into())
-                .unwrap())
+        let res = match self.guard {
+            Some(timeout) => self.handle_guarded(interaction, on_error, timeout).await,
+            None => process_interaction(
+                self.handler.as_ref(),
+                &interaction,
+                &self.middleware,
+                &on_error,
+                &self.state,
+            ),
+        };
+
+        let res = res.map_err(infrastructure_failure)?;
+
+        #[cfg(feature = "download")]
+        if res.followup.is_some() {
+            return self.respond_with_followup(callback, res).await;
+        }
+
+        Ok(HttpResponseParts::from_result(Ok(encode(
+            without_followup(res),
+            self.safe_mentions,
+        ))))
+    }
+
+    /// Sends a response along with its followup, in order, as described for
+    /// `Response::UpdateWithFollowup`: the response through the interaction's callback endpoint,
+    /// and once Discord has accepted it, the followup. Discord's request is then answered with
+    /// 202 Accepted.
+    #[cfg(feature = "download")]
+    async fn respond_with_followup(
+        &self,
+        (interaction_id, application_id, token): (crate::Snowflake, crate::Snowflake, String),
+        mut res: InteractionResponse,
+    ) -> Result<HttpResponseParts, StatusCode> {
+        let followup = res.followup.take();
+        let (content_type, body) = encode(res, self.safe_mentions);
+        if let Err(err) =
+            crate::rest::create_response(&interaction_id, &token, content_type, body).await
+        {
+            tracing::error!({ %err }, "Response failed, so its followup wasn't sent");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+
+        if let Some(mut followup) = followup {
+            if self.safe_mentions {
+                suppress_mentions(&mut followup);
+            }
+            if let Err(err) = crate::rest::create_followup(&application_id, &token, &followup).await
+            {
+                tracing::error!({ %err }, "Followup failed");
+            }
+        }
+
+        Ok(HttpResponseParts::accepted())
+    }
+
+    async fn handle_guarded<E>(
+        &self,
+        interaction: InteractionRequest,
+        on_error: E,
+        timeout: Duration,
+    ) -> Result<InteractionResponse, HandlerError>
+    where
+        E: Fn(&InteractionRequest, HandlerError) -> crate::Response + Send + 'static,
+    {
+        let middleware = self.middleware.clone();
+        let state = self.state.clone();
+        let handler = self.handler.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            process_interaction(
+                handler.as_ref(),
+                &interaction,
+                &middleware,
+                &on_error,
+                &state,
+            )
+        });
+
+        match tokio::time::timeout(timeout, task).await {
+            Ok(Ok(res)) => res,
+
+            Ok(Err(err)) => {
+                tracing::error!({ %err }, "Handler failed");
+                Ok(fallback_response(&self.fallback_message))
+            }
+
+            Err(_) => {
+                tracing::error!(?timeout, "Handler timed out");
+                Ok(fallback_response(&self.fallback_message))
+            }
         }
+    }
+}
+
+/// Logs a `HandlerError::infrastructure`, which is answered with a 500 Internal Server Error.
+fn infrastructure_failure(err: HandlerError) -> StatusCode {
+    tracing::error!({ %err }, "Infrastructure failure");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// The ephemeral message sent when handling fails without an error to reply to.
+fn fallback_response(text: &str) -> InteractionResponse {
+    Message::new()
+        .text(text)
+        .ephemeral()
+        .try_into()
+        .expect("A text message is always a valid response!")
+}
 
-        Err(code) => Ok(Response::builder()
-            .status(code)
-            .body("Error when handling request.".into())
-            .unwrap()),
+/// Replaces a response that needs a followup, which only a `Runner` can send, with the fallback
+/// message.
+fn without_followup(res: InteractionResponse) -> InteractionResponse {
+    match res.followup {
+        Some(_) => {
+            tracing::error!("Responses with followups can only be sent by a runner");
+            fallback_response(DEFAULT_FALLBACK_MESSAGE)
+        }
+        None => res,
     }
 }
 
-fn handle_body<T>(req_json: &str) -> Option<String>
+/// Creates the function that turns a handler's error into the response sent in its place.
+fn error_handler<U>(
+    fallback_message: String,
+    on_unknown: Option<Arc<UnknownFallback>>,
+    on_unhandled: Option<U>,
+) -> impl Fn(&InteractionRequest, HandlerError) -> crate::Response
 where
-    T: InteractionHandler + Sync,
+    U: Fn(),
 {
+    move |req, err| {
+        if let (true, Some(on_unhandled)) = (err.unknown, &on_unhandled) {
+            on_unhandled();
+        }
+
+        if let (true, Some(on_unknown)) = (err.unknown, &on_unknown) {
+            tracing::warn!("Handler didn't recognize the interaction");
+            return on_unknown(req);
+        }
+
+        tracing::error!({ %err }, "Handler returned an error");
+        let text = err.reply.as_deref().unwrap_or(&fallback_message);
+        crate::Response::Message(Message::new().text(text).ephemeral())
+    }
+}
+
+/// Parses a verified request body into an interaction.
+pub(crate) fn parse(
+    req_json: &str,
+    on_unhandled: Option<&UnhandledHook>,
+) -> Result<InteractionRequest, StatusCode> {
     tracing::info!({ %req_json }, "Request JSON");
 
-    match serde_json::from_str::<super::discord_types::InteractionRequest>(req_json) {
-        Ok(interaction) => {
-            let res = super::handler::handle_interaction::<T>(&interaction);
+    // A body that can't be parsed as an interaction has no valid JSON response, so it's reported
+    // as a bad request rather than answered with an empty object.
+    InteractionRequest::from_json(req_json).map_err(|_| {
+        if is_unsupported_type(req_json) {
+            tracing::warn!("Unsupported interaction type");
+            if let Some(hook) = on_unhandled {
+                hook(req_json);
+            }
+        }
+        StatusCode::BAD_REQUEST
+    })
+}
+
+/// Serializes a response, returning its content type and body.
+pub(crate) fn encode(mut res: InteractionResponse, safe_mentions: bool) -> (String, Vec<u8>) {
+    if safe_mentions {
+        suppress_mentions(&mut res);
+    }
+
+    let res_json = json!(res).to_string();
 
-            let res_json = json!(res).to_string();
+    tracing::info!({ %res_json }, "Response JSON");
 
-            tracing::info!({ %res_json }, "Response JSON");
+    if res.files.is_empty() {
+        ("application/json".to_string(), res_json.into_bytes())
+    } else {
+        multipart(&res_json, &res.files)
+    }
+}
 
-            Some(res_json)
+/// Whether a request that failed to parse has an interaction type that this library doesn't
+/// support, as opposed to being malformed.
+fn is_unsupported_type(req_json: &str) -> bool {
+    let r#type = serde_json::from_str::<serde_json::Value>(req_json)
+        .ok()
+        .and_then(|req| req.get("type").cloned());
+
+    match r#type {
+        Some(r#type) => {
+            r#type.is_u64() && serde_json::from_value::<InteractionType>(r#type).is_err()
         }
+        None => false,
+    }
+}
+
+/// Allows no mentions in a message response, unless the message sets its own allowed mentions.
+pub(crate) fn suppress_mentions(res: &mut InteractionResponse) {
+    let is_message = matches!(
+        res.r#type,
+        InteractionCallbackType::ChannelMessageWithSource | InteractionCallbackType::UpdateMessage
+    );
+
+    if let (true, Some(data)) = (is_message, res.data.as_mut()) {
+        data.allowed_mentions.get_or_insert(AllowedMentions {
+            parse: Vec::new(),
+            users: Vec::new(),
+            roles: Vec::new(),
+            replied_user: None,
+        });
+    }
+}
+
+/// Encodes a response with files as multipart form data, with the JSON payload in a `payload_json`
+/// part, followed by a `files[n]` part for each file. Returns the content type (which names the
+/// boundary between parts) along with the body.
+pub(crate) fn multipart(payload_json: &str, files: &[FileData]) -> (String, Vec<u8>) {
+    let contains = |haystack: &[u8], needle: &str| {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    };
+
+    // The boundary must not appear in any part.
+    let boundary = (0..)
+        .map(|n| format!("discord-interaction-boundary-{}", n))
+        .find(|b| {
+            !contains(payload_json.as_bytes(), b) && files.iter().all(|f| !contains(&f.data, b))
+        })
+        .unwrap();
 
-        Err(_) => None,
+    let header = |disposition: &str, content_type: &str| {
+        format!(
+            "--{}\r\nContent-Disposition: form-data; {}\r\nContent-Type: {}\r\n\r\n",
+            boundary, disposition, content_type
+        )
+    };
+
+    let mut body = header("name=\"payload_json\"", "application/json").into_bytes();
+    body.extend(payload_json.as_bytes());
+    body.extend(b"\r\n");
+
+    for (i, file) in files.iter().enumerate() {
+        let filename = file.filename.replace(['"', '\r', '\n'], "");
+        let disposition = format!("name=\"files[{}]\"; filename=\"{}\"", i, filename);
+        body.extend(header(&disposition, "application/octet-stream").as_bytes());
+        body.extend(&file.data);
+        body.extend(b"\r\n");
     }
+
+    body.extend(format!("--{}--\r\n", boundary).as_bytes());
+
+    (format!("multipart/form-data; boundary={}", boundary), body)
 }
 
-fn verify(body: &str, headers: &HeaderMap, app_pk: &str) -> Result<(), StatusCode> {
-    let application_public_key: [u8; PUBLIC_KEY_LENGTH] = hex::decode(&app_pk)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+/// Decodes an application public key from hex, failing if it isn't a valid Ed25519 public key.
+fn parse_public_key(app_pk: &str) -> Result<VerifyingKey, InvalidPublicKey> {
+    let invalid = || InvalidPublicKey {
+        key: app_pk.to_string(),
+    };
+    let application_public_key: [u8; PUBLIC_KEY_LENGTH] = hex::decode(app_pk)
+        .map_err(|_| invalid())?
         .try_into()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| invalid())?;
 
-    let verifier = VerifyingKey::from_bytes(&application_public_key)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    VerifyingKey::from_bytes(&application_public_key).map_err(|_| invalid())
+}
+
+/// Verifies a request's signature, returning its body as text.
+pub(crate) fn verify<'a, H>(
+    body: &'a [u8],
+    headers: &H,
+    verifier: &VerifyingKey,
+) -> Result<&'a str, StatusCode>
+where
+    H: Headers + ?Sized,
+{
+    let body = std::str::from_utf8(body).map_err(|_| StatusCode::BAD_REQUEST)?;
 
     let timestamp = headers
-        .get("X-Signature-Timestamp")
-        .ok_or(StatusCode::BAD_REQUEST)?
-        .to_str()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+        .header("X-Signature-Timestamp")
+        .ok_or(StatusCode::BAD_REQUEST)?;
 
     let signature_str = headers
-        .get("X-Signature-Ed25519")
-        .ok_or(StatusCode::BAD_REQUEST)?
-        .to_str()
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+        .header("X-Signature-Ed25519")
+        .ok_or(StatusCode::BAD_REQUEST)?;
 
-    let signature: [u8; 64] = hex::decode(&signature_str)
+    let signature: [u8; 64] = hex::decode(signature_str)
         .map_err(|_| StatusCode::BAD_REQUEST)?
         .try_into()
         .map_err(|_| StatusCode::BAD_REQUEST)?;
@@ -99,5 +839,243 @@ fn verify(body: &str, headers: &HeaderMap, app_pk: &str) -> Result<(), StatusCod
 
     verifier
         .verify(&msg, &Signature::from_bytes(&signature))
-        .map_err(|_| StatusCode::UNAUTHORIZED)
+        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ApplicationCommand, FileUpload};
+    use serde_json::Value;
+
+    /// A valid application public key, for runners in insecure local mode, which don't verify
+    /// requests with it.
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+    #[derive(Default)]
+    struct TestHandler;
+
+    impl InteractionHandler for TestHandler {
+        fn handle_application_command(
+            &self,
+            ac: ApplicationCommand,
+        ) -> Result<crate::Response, HandlerError> {
+            let msg = Message::new().text("Hello @everyone");
+            match ac.command_name.as_str() {
+                "hello" => Ok(crate::Response::Message(msg)),
+                "file" => Ok(crate::Response::Message(
+                    msg.file(FileUpload::new("hello.txt", b"Hello!".to_vec())),
+                )),
+                "ping" => Ok(crate::Response::Message(msg.ping_users())),
+                "count" => {
+                    let count: u32 = "many".parse()?;
+                    Ok(crate::Response::Message(msg.text(&count.to_string())))
+                }
+                "database" => Err(HandlerError::infrastructure("Database unreachable")),
+                "panic" => panic!("Handler bug"),
+                "empty_select" => Ok(crate::Response::Message(
+                    msg.select(crate::SelectMenu::new("color")),
+                )),
+                "deferred_update" => Ok(crate::Response::DeferredUpdate),
+                #[cfg(feature = "download")]
+                "claim" => Ok(claim()),
+                _ => Err(HandlerError::unknown()),
+            }
+        }
+
+        #[cfg(feature = "download")]
+        fn handle_message_component(
+            &self,
+            _mc: crate::MessageComponent,
+        ) -> Result<crate::Response, HandlerError> {
+            Ok(claim())
+        }
+    }
+
+    #[cfg(feature = "download")]
+    fn claim() -> crate::Response {
+        crate::Response::update_with_ephemeral_followup(
+            Message::new().text("Claimed by mason"),
+            Message::new().text("You claimed it!"),
+        )
+    }
+
+    fn command(name: &str) -> String {
+        json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 2,
+            "user": { "id": "53908232506183680", "username": "mason" },
+            "data": { "id": "771825006014889984", "name": name, "type": 1 },
+        })
+        .to_string()
+    }
+
+    fn handle(runner: &Runner<TestHandler>, body: &str) -> HttpResponseParts {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let headers: [(&str, &str); 0] = [];
+        runtime.block_on(runner.handle_http(&headers[..], body.as_bytes()))
+    }
+
+    fn runner() -> Runner<TestHandler> {
+        Runner::new(PUBLIC_KEY).insecure_local_mode()
+    }
+
+    /// Signs the given body, returning the headers that Discord would send it with.
+    fn signed_headers(body: &str) -> [(&'static str, String); 2] {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        // The secret key that `PUBLIC_KEY` belongs to, from RFC 8032's first test vector.
+        let secret = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+        let signing_key = SigningKey::from_bytes(&hex::decode(secret).unwrap().try_into().unwrap());
+        let signature = signing_key.sign(format!("1700000000{}", body).as_bytes());
+        [
+            ("X-Signature-Timestamp", "1700000000".to_string()),
+            ("X-Signature-Ed25519", hex::encode(signature.to_bytes())),
+        ]
+    }
+
+    #[test]
+    fn json_content_type() {
+        let res = handle(&runner(), &command("hello"));
+        assert_eq!(res.status, 200);
+        assert_eq!(res.content_type, "application/json");
+        serde_json::from_slice::<Value>(&res.body).unwrap();
+
+        let (content_type, _) = encode(Message::new().text("Hi").try_into().unwrap(), true);
+        assert_eq!(content_type, "application/json");
+    }
+
+    #[test]
+    fn multipart_content_type() {
+        let res = handle(&runner(), &command("file"));
+        assert_eq!(res.status, 200);
+
+        let boundary = res
+            .content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let body = String::from_utf8(res.body).unwrap();
+        assert!(body.starts_with(&format!("--{}\r\n", boundary)));
+        assert!(body.ends_with(&format!("--{}--\r\n", boundary)));
+        assert!(body.contains("filename=\"hello.txt\""));
+    }
+
+    fn json_body(res: HttpResponseParts) -> Value {
+        serde_json::from_slice(&res.body).unwrap()
+    }
+
+    #[test]
+    fn mentions_are_suppressed_by_default() {
+        let res = json_body(handle(&runner(), &command("hello")));
+        assert_eq!(res["data"]["allowed_mentions"], json!({ "parse": [] }));
+    }
+
+    #[test]
+    fn discord_default_mentions() {
+        let res = json_body(handle(
+            &runner().discord_default_mentions(),
+            &command("hello"),
+        ));
+        assert!(res["data"].get("allowed_mentions").is_none());
+    }
+
+    #[test]
+    fn opting_into_mentions() {
+        let res = json_body(handle(&runner(), &command("ping")));
+        assert_eq!(res["data"]["allowed_mentions"]["parse"], json!(["users"]));
+    }
+
+    #[test]
+    fn handler_errors_get_the_fallback_message() {
+        let res = handle(&runner().fallback_message("Oops"), &command("count"));
+        assert_eq!(res.status, 200);
+        let res = json_body(res);
+        assert_eq!(res["data"]["content"], "Oops");
+        assert_eq!(res["data"]["flags"], 64);
+    }
+
+    #[test]
+    fn infrastructure_errors_are_server_errors() {
+        let res = handle(&runner(), &command("database"));
+        assert_eq!(res.status, 500);
+
+        let res = handle(
+            &runner().guard(Duration::from_secs(1)),
+            &command("database"),
+        );
+        assert_eq!(res.status, 500);
+    }
+
+    #[test]
+    fn panics_and_invalid_responses_get_the_fallback_message() {
+        for name in ["panic", "empty_select", "deferred_update"] {
+            let res = handle(&runner().fallback_message("Oops"), &command(name));
+            assert_eq!(res.status, 200, "{}", name);
+            assert_eq!(json_body(res)["data"]["content"], "Oops", "{}", name);
+        }
+    }
+
+    #[test]
+    fn failing_error_replies_get_a_fixed_reply() {
+        let panicking = runner().on_unknown(|_| panic!("Fallback bug"));
+        let body = json_body(handle(&panicking, &command("unknown")));
+        assert_eq!(body["data"]["content"], "Something went wrong.");
+        assert_eq!(body["data"]["flags"], crate::message_flags::EPHEMERAL);
+
+        let invalid =
+            runner().on_unknown(|_| crate::Response::Message(Message::new().row(Vec::new())));
+        let body = json_body(handle(&invalid, &command("unknown")));
+        assert_eq!(body["data"]["content"], "Something went wrong.");
+    }
+
+    #[test]
+    fn invalid_public_key() {
+        let err = Runner::<TestHandler>::try_new("not a key").err().unwrap();
+        assert_eq!(err.key, "not a key");
+        assert!(PublicKey::parse(&PUBLIC_KEY[2..]).is_err());
+
+        let headers: [(&str, &str); 0] = [];
+        let res = handle_interaction("not a key", &headers[..], b"{}", &TestHandler);
+        assert_eq!(res.status, 500);
+    }
+
+    #[test]
+    fn preparsed_public_key() {
+        let body = command("hello");
+        let headers = signed_headers(&body);
+
+        let public_key = PublicKey::parse(PUBLIC_KEY).unwrap();
+        let res =
+            handle_interaction_with_key(&public_key, &headers[..], body.as_bytes(), &TestHandler);
+        assert_eq!(res.status, 200);
+
+        let res = handle_interaction_with_key(&public_key, &headers[..], b"{}", &TestHandler);
+        assert_eq!(res.status, 401);
+    }
+
+    #[cfg(feature = "download")]
+    #[test]
+    fn followups_need_a_runner() {
+        let body = include_str!("../tests/fixtures/component.json");
+        let headers = signed_headers(body);
+        let res = handle_interaction(PUBLIC_KEY, &headers[..], body.as_bytes(), &TestHandler);
+        assert_eq!(res.status, 200);
+        let res = json_body(res);
+        assert_eq!(res["type"], 4);
+        assert_eq!(res["data"]["content"], "Something went wrong.");
+
+        // Application commands have no message to update.
+        let res = json_body(handle(
+            &runner().fallback_message("Oops"),
+            &command("claim"),
+        ));
+        assert_eq!(res["data"]["content"], "Oops");
+    }
 }
@@ -0,0 +1,47 @@
+/*!
+ * Serving interactions from an [axum](https://docs.rs/axum) app, for self-hosted bots. Mount the
+ * interactions endpoint at the path registered with Discord:
+ *
+ * ```ignore
+ * let app = axum::Router::new()
+ *     .route("/interactions", discord_interaction::axum::route(&public_key, MyHandler));
+ * ```
+ *
+ * Requires the `axum` feature.
+ */
+
+use super::auth::Runner;
+use super::handler::InteractionHandler;
+use ::axum::body::Bytes;
+use ::axum::http::header::CONTENT_TYPE;
+use ::axum::http::{HeaderMap, StatusCode};
+use ::axum::response::IntoResponse;
+use ::axum::routing::{post, MethodRouter};
+use std::sync::Arc;
+
+/// Creates a route that verifies and handles interactions POSTed to it, using the given
+/// application public key and handler.
+pub fn route<T, S>(public_key: &str, handler: T) -> MethodRouter<S>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    runner_route(Runner::with_handler(public_key, handler))
+}
+
+/// Creates a route that handles interactions POSTed to it with a configured runner, e.g. one with
+/// middleware or application state.
+pub fn runner_route<T, S>(runner: Runner<T>) -> MethodRouter<S>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    let runner = Arc::new(runner);
+
+    post(move |headers: HeaderMap, body: Bytes| async move {
+        let res = runner.handle_http(&headers, &body).await;
+        let status = StatusCode::from_u16(res.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        (status, [(CONTENT_TYPE, res.content_type)], res.body).into_response()
+    })
+}
@@ -0,0 +1,182 @@
+use super::error::HandlerError;
+use super::snowflake::Snowflake;
+use super::user_types::{ApplicationCommand, CommandOptionValue, Response};
+use serde::Serialize;
+use serde_repr::Serialize_repr;
+use std::future::Future;
+
+/// The definition of a slash command, in the form that Discord expects when registering
+/// application commands. Definitions are generated by `#[slash_command]`, and can be collected
+/// from a router with `Router::definitions`.
+#[derive(Serialize, Clone, Debug)]
+pub struct CommandDefinition {
+    /// The command's name, 1-32 lowercase characters.
+    pub name: String,
+    /// The command's description, 1-100 characters.
+    pub description: String,
+    pub options: Vec<OptionDefinition>,
+}
+
+/// The definition of one of a slash command's options.
+#[derive(Serialize, Clone, Debug)]
+pub struct OptionDefinition {
+    #[serde(rename = "type")]
+    pub kind: OptionType,
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// The types of value a command option can take.
+#[derive(Serialize_repr, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum OptionType {
+    String = 3,
+    Integer = 4,
+    Boolean = 5,
+    User = 6,
+    Channel = 7,
+    Role = 8,
+    Mentionable = 9,
+    Number = 10,
+    Attachment = 11,
+}
+
+/// A slash command, with both its definition and its handler. This is implemented by
+/// `#[slash_command]`, and commands are registered with `Router::slash_command`.
+pub trait SlashCommand {
+    fn definition(&self) -> CommandDefinition;
+
+    fn handle(&self, ac: ApplicationCommand) -> Result<Response, HandlerError>;
+}
+
+/// A type that a command option's value can be read as, for the arguments of `#[slash_command]`
+/// functions.
+pub trait FromOptionValue: Sized {
+    /// The option type that the argument is registered with.
+    const TYPE: OptionType;
+
+    /// Reads the option's value, or returns `None` if it has a different type.
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self>;
+}
+
+impl FromOptionValue for String {
+    const TYPE: OptionType = OptionType::String;
+
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        match value {
+            CommandOptionValue::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromOptionValue for i64 {
+    const TYPE: OptionType = OptionType::Integer;
+
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        match value {
+            CommandOptionValue::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl FromOptionValue for f64 {
+    const TYPE: OptionType = OptionType::Number;
+
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        match value {
+            CommandOptionValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl FromOptionValue for bool {
+    const TYPE: OptionType = OptionType::Boolean;
+
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        match value {
+            CommandOptionValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+/// Snowflake arguments are registered as user options.
+impl FromOptionValue for Snowflake {
+    const TYPE: OptionType = OptionType::User;
+
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        match value {
+            CommandOptionValue::User(id) => Some(id.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl ApplicationCommand {
+    /// Reads the option with the given name as a `T`, or `None` if it wasn't provided. Fails if
+    /// the option has a different type.
+    pub fn option_value<T: FromOptionValue>(&self, name: &str) -> Result<Option<T>, HandlerError> {
+        match self.option(name) {
+            Some(value) => match T::from_option_value(value) {
+                Some(value) => Ok(Some(value)),
+                None => Err(HandlerError::new(&format!(
+                    "Option `{}` should have type {:?}",
+                    name,
+                    T::TYPE
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the required option with the given name as a `T`. Fails if the option wasn't
+    /// provided, or has a different type.
+    pub fn required_option<T: FromOptionValue>(&self, name: &str) -> Result<T, HandlerError> {
+        self.option_value(name)?
+            .ok_or_else(|| HandlerError::new(&format!("Missing option `{}`", name)))
+    }
+}
+
+/// Runs an async `#[slash_command]` function to completion, blocking the current thread. Blocking
+/// requires a multi-threaded Tokio runtime, as used by `run` and `#[tokio::main]`; on any other
+/// runtime, or outside of one, the command fails with a `HandlerError` instead. This isn't
+/// available on wasm32, where there are no threads to block.
+#[doc(hidden)]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn block_on<F: Future>(future: F) -> Result<F::Output, HandlerError> {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        _ => Err(HandlerError::new(
+            "Async slash commands must run on a multi-threaded Tokio runtime!",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn block_on_multi_thread_runtime() {
+        let runtime = Builder::new_multi_thread().build().unwrap();
+        let res = runtime.block_on(async { block_on(async { 1 }) });
+        assert_eq!(res.unwrap(), 1);
+    }
+
+    #[test]
+    fn block_on_current_thread_runtime() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let res = runtime.block_on(async { block_on(async { 1 }) });
+        assert!(res.is_err());
+        assert!(block_on(async { 1 }).is_err());
+    }
+}
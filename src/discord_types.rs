@@ -3,15 +3,78 @@
  * match the JSON structure established by the Discord API.
  */
 
-use serde::{Deserialize, Serialize};
+use super::snowflake::Snowflake;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::HashMap;
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct InteractionRequest {
+    pub id: Snowflake,
+    pub application_id: Snowflake,
+    pub token: String,
     pub r#type: InteractionType,
     pub data: Option<InteractionData>,
+    pub guild_id: Option<Snowflake>,
+    pub channel_id: Option<Snowflake>,
+    pub channel: Option<Channel>,
     pub member: Option<GuildMember>,
+    pub user: Option<User>,
     pub message: Option<Message>,
+    pub locale: Option<String>,
+    pub guild_locale: Option<String>,
+    pub app_permissions: Option<String>,
+    pub context: Option<u8>,
+    pub authorizing_integration_owners: Option<HashMap<String, Snowflake>>,
+    #[serde(default)]
+    pub entitlements: Vec<Entitlement>,
+    /// The request's `data`, as it was sent, for reading fields that aren't modeled here. This is
+    /// only filled in by `from_json`, and is `Null` for requests without data.
+    #[serde(skip)]
+    pub raw_data: serde_json::Value,
+}
+
+impl InteractionRequest {
+    /// Parses an interaction request from its JSON representation, as sent by Discord. This is
+    /// useful for testing handlers against captured payloads, since the handler payload types
+    /// (e.g. `ApplicationCommand`) can be built from a parsed request with `From`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        let raw_data = value
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or_default();
+
+        let mut req: Self = serde_json::from_value(value)?;
+        req.data = serde_json::from_value(raw_data.clone())?;
+        req.raw_data = raw_data;
+        Ok(req)
+    }
+
+    /// The user that caused the interaction. Discord sends this as part of `member` for
+    /// interactions in guilds, and as `user` for interactions in DMs.
+    pub fn invoking_user(&self) -> Option<&User> {
+        self.member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(self.user.as_ref())
+    }
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Entitlement {
+    pub id: Snowflake,
+    pub sku_id: Snowflake,
+    pub application_id: Snowflake,
+    pub user_id: Option<Snowflake>,
+    pub guild_id: Option<Snowflake>,
+    pub r#type: u8,
+    #[serde(default)]
+    pub deleted: bool,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub consumed: Option<bool>,
 }
 
 #[derive(Deserialize_repr, PartialEq, Debug)]
@@ -20,81 +83,570 @@ pub enum InteractionType {
     Ping = 1,
     ApplicationCommand = 2,
     MessageComponent = 3,
+    ApplicationCommandAutocomplete = 4,
     ModalSubmit = 5,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct InteractionData {
+    pub id: Option<Snowflake>,
     pub name: Option<String>,
+    pub r#type: Option<ApplicationCommandType>,
+    pub target_id: Option<Snowflake>,
     pub custom_id: Option<String>,
     pub components: Option<Vec<Component>>,
+    pub values: Option<Vec<String>>,
+    pub options: Option<Vec<ApplicationCommandOption>>,
+    pub resolved: Option<ResolvedData>,
+}
+
+#[derive(Deserialize_repr, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum ApplicationCommandType {
+    ChatInput = 1,
+    User = 2,
+    Message = 3,
+    PrimaryEntryPoint = 4,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct ApplicationCommandOption {
+    pub name: String,
+    pub r#type: ApplicationCommandOptionType,
+    pub value: Option<serde_json::Value>,
+    pub options: Option<Vec<ApplicationCommandOption>>,
+    pub focused: Option<bool>,
+}
+
+#[derive(Deserialize_repr, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum ApplicationCommandOptionType {
+    SubCommand = 1,
+    SubCommandGroup = 2,
+    String = 3,
+    Integer = 4,
+    Boolean = 5,
+    User = 6,
+    Channel = 7,
+    Role = 8,
+    Mentionable = 9,
+    Number = 10,
+    Attachment = 11,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct ResolvedData {
+    pub users: Option<HashMap<Snowflake, User>>,
+    pub members: Option<HashMap<Snowflake, GuildMember>>,
+    pub roles: Option<HashMap<Snowflake, Role>>,
+    pub channels: Option<HashMap<Snowflake, Channel>>,
+    pub messages: Option<HashMap<Snowflake, Message>>,
+    pub attachments: Option<HashMap<Snowflake, Attachment>>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct GuildMember {
-    pub user: User,
+    /// Absent for the partial members included in resolved data.
+    pub user: Option<User>,
+    pub nick: Option<String>,
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<Snowflake>,
+    pub joined_at: Option<String>,
+    pub premium_since: Option<String>,
+    pub permissions: Option<String>,
+    pub communication_disabled_until: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Role {
+    pub id: Snowflake,
+    pub name: String,
+    #[serde(default)]
+    pub color: u32,
+    #[serde(default)]
+    pub hoist: bool,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub unicode_emoji: Option<String>,
+    #[serde(default)]
+    pub position: i32,
+    #[serde(default)]
+    pub permissions: String,
+    #[serde(default)]
+    pub managed: bool,
+    #[serde(default)]
+    pub mentionable: bool,
+    /// Only present for roles managed by a bot, an integration or server subscriptions.
+    #[serde(default)]
+    pub tags: Option<RoleTags>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct RoleTags {
+    pub bot_id: Option<Snowflake>,
+    pub integration_id: Option<Snowflake>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Channel {
+    pub id: Snowflake,
+    pub r#type: u8,
+    pub name: Option<String>,
+    pub parent_id: Option<Snowflake>,
+    pub permissions: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Attachment {
+    pub id: Snowflake,
+    pub filename: String,
+    pub description: Option<String>,
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    pub height: Option<u32>,
+    pub width: Option<u32>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 pub struct Message {
+    pub id: Snowflake,
     pub content: String,
+    pub author: Option<User>,
+    pub timestamp: Option<String>,
+    pub edited_timestamp: Option<String>,
+    pub flags: Option<u64>,
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
+    #[serde(default)]
+    pub components: Vec<Component>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    pub poll: Option<Poll>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct Poll {
+    pub question: PollMedia,
+    pub answers: Vec<PollAnswer>,
+    /// The poll's duration in hours, when creating a poll.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+    #[serde(default)]
+    pub allow_multiselect: bool,
+    /// When the poll ends, for polls read from a message.
+    #[serde(skip_serializing)]
+    pub expiry: Option<String>,
+    #[serde(skip_serializing)]
+    pub results: Option<PollResults>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct PollMedia {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct PollAnswer {
+    /// Assigned by Discord, so absent when creating a poll.
+    #[serde(skip_serializing)]
+    pub answer_id: Option<u32>,
+    pub poll_media: PollMedia,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct PollResults {
+    pub is_finalized: bool,
+    pub answer_counts: Vec<PollAnswerCount>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
+pub struct PollAnswerCount {
+    pub id: u32,
+    pub count: u32,
+    pub me_voted: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct Embed {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer: Option<EmbedFooter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<EmbedMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<EmbedMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<EmbedAuthor>,
+    #[serde(default)]
+    pub fields: Vec<EmbedField>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct EmbedFooter {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct EmbedMedia {
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct EmbedAuthor {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub inline: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
 pub struct User {
-    pub id: String,
+    pub id: Snowflake,
+    #[serde(default)]
+    pub username: String,
+    pub global_name: Option<String>,
+    pub discriminator: Option<String>,
+    pub avatar: Option<String>,
+    pub banner: Option<String>,
+    pub accent_color: Option<u32>,
 }
 
-#[derive(Serialize, PartialEq, Debug)]
+#[derive(PartialEq, Debug)]
 pub struct InteractionResponse {
     pub r#type: InteractionCallbackType,
     pub data: Option<InteractionCallbackData>,
+    /// Files uploaded with the response. These aren't part of the JSON payload; a response with
+    /// files is sent as multipart form data instead.
+    pub files: Vec<FileData>,
+    /// A JSON payload to send in place of `type` and `data`, for responses that use Discord
+    /// features not modeled here.
+    pub raw: Option<serde_json::Value>,
+    /// A followup message to create once the response has been sent, for responses that take two
+    /// API calls. Only its `data` and `files` are sent.
+    pub followup: Option<Box<InteractionResponse>>,
 }
 
-#[derive(Serialize_repr, PartialEq, Debug)]
+impl Serialize for InteractionResponse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(raw) = &self.raw {
+            return raw.serialize(serializer);
+        }
+
+        let mut res = serializer.serialize_struct("InteractionResponse", 2)?;
+        res.serialize_field("type", &self.r#type)?;
+        res.serialize_field("data", &self.data)?;
+        res.end()
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct FileData {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug)]
 #[repr(u8)]
 pub enum InteractionCallbackType {
     Pong = 1,
     ChannelMessageWithSource = 4,
+    DeferredChannelMessageWithSource = 5,
+    DeferredUpdateMessage = 6,
     UpdateMessage = 7,
+    ApplicationCommandAutocompleteResult = 8,
     Modal = 9,
+    PremiumRequired = 10,
+    LaunchActivity = 12,
 }
 
-#[derive(Serialize, PartialEq, Debug)]
+#[derive(Serialize, PartialEq, Debug, Default)]
 pub struct InteractionCallbackData {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
-    pub flags: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeds: Option<Vec<Embed>>,
+    pub flags: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Vec<Component>>,
     pub custom_id: Option<String>,
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<AttachmentUpload>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll: Option<Poll>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<CommandChoice>>,
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, PartialEq, Debug)]
+pub struct CommandChoice {
+    pub name: String,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub name_localizations: HashMap<String, String>,
+    pub value: serde_json::Value,
+}
+
+/// Metadata for a file uploaded with a response, matched to the file's multipart part by `id`.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AttachmentUpload {
+    pub id: usize,
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AllowedMentions {
+    pub parse: Vec<AllowedMentionType>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<Snowflake>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<Snowflake>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replied_user: Option<bool>,
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AllowedMentionType {
+    Roles,
+    Users,
+    Everyone,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
 pub struct Component {
     pub r#type: ComponentType,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub style: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub components: Option<Vec<Component>>,
+    /// The single component wrapped by a label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<Box<Component>>,
+    /// The values chosen in a select menu, in a modal submit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<SelectOption>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_values: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_values: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku_id: Option<Snowflake>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessory: Option<Box<Component>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<MediaGalleryItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<UnfurledMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<UnfurledMedia>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spoiler: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub divider: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct MediaGalleryItem {
+    pub media: UnfurledMedia,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub spoiler: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct UnfurledMedia {
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct SelectOption {
+    pub label: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Left out unless set, as Discord only needs the options chosen by default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Emoji {
+    /// Absent for unicode emoji.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Snowflake>,
+    /// The emoji itself for unicode emoji, or the custom emoji's name.
+    pub name: Option<String>,
+    #[serde(default)]
+    pub animated: bool,
 }
 
 #[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug, Clone)]
 #[repr(u8)]
 pub enum TextInputStyle {
     Short = 1,
+    Paragraph = 2,
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug, Clone)]
-#[repr(u8)]
+/// The type of a component. Types added to Discord since this library was written are kept as
+/// `Unknown`, so that a message containing them can still be parsed.
+#[derive(PartialEq, Debug, Clone, Default)]
 pub enum ComponentType {
-    ActionRow = 1,
-    Button = 2,
-    TextInput = 4,
+    #[default]
+    ActionRow,
+    Button,
+    StringSelect,
+    TextInput,
+    UserSelect,
+    RoleSelect,
+    MentionableSelect,
+    ChannelSelect,
+    Section,
+    TextDisplay,
+    Thumbnail,
+    MediaGallery,
+    File,
+    Separator,
+    Container,
+    Label,
+    Unknown(u8),
+}
+
+impl From<u8> for ComponentType {
+    fn from(r#type: u8) -> Self {
+        match r#type {
+            1 => ComponentType::ActionRow,
+            2 => ComponentType::Button,
+            3 => ComponentType::StringSelect,
+            4 => ComponentType::TextInput,
+            5 => ComponentType::UserSelect,
+            6 => ComponentType::RoleSelect,
+            7 => ComponentType::MentionableSelect,
+            8 => ComponentType::ChannelSelect,
+            9 => ComponentType::Section,
+            10 => ComponentType::TextDisplay,
+            11 => ComponentType::Thumbnail,
+            12 => ComponentType::MediaGallery,
+            13 => ComponentType::File,
+            14 => ComponentType::Separator,
+            17 => ComponentType::Container,
+            18 => ComponentType::Label,
+            other => ComponentType::Unknown(other),
+        }
+    }
+}
+
+impl From<&ComponentType> for u8 {
+    fn from(r#type: &ComponentType) -> Self {
+        match r#type {
+            ComponentType::ActionRow => 1,
+            ComponentType::Button => 2,
+            ComponentType::StringSelect => 3,
+            ComponentType::TextInput => 4,
+            ComponentType::UserSelect => 5,
+            ComponentType::RoleSelect => 6,
+            ComponentType::MentionableSelect => 7,
+            ComponentType::ChannelSelect => 8,
+            ComponentType::Section => 9,
+            ComponentType::TextDisplay => 10,
+            ComponentType::Thumbnail => 11,
+            ComponentType::MediaGallery => 12,
+            ComponentType::File => 13,
+            ComponentType::Separator => 14,
+            ComponentType::Container => 17,
+            ComponentType::Label => 18,
+            ComponentType::Unknown(other) => *other,
+        }
+    }
+}
+
+impl Serialize for ComponentType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.into())
+    }
+}
+
+impl<'de> Deserialize<'de> for ComponentType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(ComponentType::from)
+    }
 }
 
 #[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug, Clone)]
 #[repr(u8)]
 pub enum ButtonStyle {
     Primary = 1,
+    Secondary = 2,
+    Success = 3,
+    Danger = 4,
+    Link = 5,
+    Premium = 6,
 }
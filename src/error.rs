@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// An error returned by an `InteractionHandler`. The error is logged, and the user is sent an
+/// ephemeral message in place of the handler's response: the `Runner`'s fallback message by
+/// default, or the error's own reply if it has one.
+///
+/// Any `std::error::Error` converts into a `HandlerError`, so handlers can use `?` on fallible
+/// calls. Handlers that don't recognize an interaction, e.g. an unknown command name or custom
+/// id, can return `HandlerError::unknown()` to defer to the runner's `on_unknown` fallback.
+///
+/// Failures of the bot's own infrastructure, e.g. a database that can't be reached, can instead
+/// be returned as `HandlerError::infrastructure`, which is answered with a 500 Internal Server
+/// Error rather than a reply, so that they show up as server errors in monitoring. Discord then
+/// tells the user that the interaction failed.
+#[derive(Debug)]
+pub struct HandlerError {
+    /// A description of the error, for the logs.
+    pub message: String,
+    /// The text shown to the user instead of the runner's fallback message.
+    pub reply: Option<String>,
+    /// If true, the handler didn't recognize the interaction. See `Runner::on_unknown`.
+    pub unknown: bool,
+    /// If true, the error is a failure of the bot's infrastructure, answered with a 500 Internal
+    /// Server Error instead of a reply.
+    pub infrastructure: bool,
+}
+
+impl HandlerError {
+    /// Creates a new error, with the given description for the logs.
+    pub fn new(message: &str) -> Self {
+        HandlerError {
+            message: message.to_string(),
+            reply: None,
+            unknown: false,
+            infrastructure: false,
+        }
+    }
+
+    /// Creates an error for an interaction that the handler doesn't recognize.
+    pub fn unknown() -> Self {
+        HandlerError {
+            unknown: true,
+            ..HandlerError::new("Unknown interaction")
+        }
+    }
+
+    /// Creates an error for a failure of the bot's infrastructure, with the given description for
+    /// the logs. Middleware still sees a response in its place, but it is discarded.
+    pub fn infrastructure(message: &str) -> Self {
+        HandlerError {
+            infrastructure: true,
+            ..HandlerError::new(message)
+        }
+    }
+
+    /// Sets the text shown to the user, e.g. to explain what they should do differently.
+    pub fn reply(mut self, text: &str) -> Self {
+        self.reply = Some(text.to_string());
+        self
+    }
+}
+
+impl<E> From<E> for HandlerError
+where
+    E: std::error::Error,
+{
+    fn from(err: E) -> Self {
+        HandlerError::new(&err.to_string())
+    }
+}
+
+impl fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// An application public key that isn't a valid hex-encoded Ed25519 public key.
+#[derive(Debug)]
+pub struct InvalidPublicKey {
+    /// The key, as it was given.
+    pub key: String,
+}
+
+impl fmt::Display for InvalidPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid application public key `{}`: expected 64 hex characters",
+            self.key
+        )
+    }
+}
+
+impl std::error::Error for InvalidPublicKey {}
+
+/// An error calling Discord's HTTP API on the bot's behalf, e.g. with
+/// `ApplicationCommand::fetch_user`.
+#[cfg(feature = "download")]
+#[derive(Debug)]
+pub enum ApiError {
+    /// The runner wasn't given a bot token to call the API with. See `Runner::bot_token`.
+    MissingToken,
+    /// The request couldn't be sent, or Discord answered it with an error status.
+    Request(reqwest::Error),
+    /// Discord's answer couldn't be parsed.
+    InvalidResponse(serde_json::Error),
+}
+
+#[cfg(feature = "download")]
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::MissingToken => f.write_str("No bot token to call Discord's API with"),
+            ApiError::Request(err) => write!(f, "Discord API request failed: {}", err),
+            ApiError::InvalidResponse(err) => write!(f, "Invalid Discord API response: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "download")]
+impl std::error::Error for ApiError {}
+
+#[cfg(feature = "download")]
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        ApiError::Request(err)
+    }
+}
+
+#[cfg(feature = "download")]
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::InvalidResponse(err)
+    }
+}
@@ -0,0 +1,233 @@
+/*!
+ * Extractors, for writing handler functions whose arguments are the parts of the interaction they
+ * need, rather than the whole payload. For example, a command handler registered with
+ * `Router::command_handler` can be written as:
+ *
+ * ```ignore
+ * fn greet(UserId(id): UserId, Locale(locale): Locale) -> Result<Response, HandlerError> {
+ *     ...
+ * }
+ * ```
+ *
+ * Each argument is extracted from the payload with `FromPayload`, and the handler isn't called if
+ * any extraction fails. Applications can implement `FromPayload` for their own extractors.
+ */
+
+use super::command::{FromOptionValue, OptionType};
+use super::error::HandlerError;
+use super::router::Params;
+use super::snowflake::Snowflake;
+use super::user_types::{
+    ApplicationCommand, CommandOptionValue, MessageComponent, ModalSubmit, Response,
+};
+use std::any::{type_name, Any};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A value that can be extracted from a handler payload of type `P`, e.g. `ApplicationCommand`.
+pub trait FromPayload<P>: Sized {
+    fn from_payload(payload: &P) -> Result<Self, HandlerError>;
+}
+
+/// A function that can handle payloads of type `P`, given extractors as arguments. This is
+/// implemented for functions of up to six arguments that implement `FromPayload<P>`, returning
+/// `Result<Response, HandlerError>`.
+pub trait Handler<P, Args> {
+    fn call(&self, payload: &P) -> Result<Response, HandlerError>;
+}
+
+macro_rules! impl_handler {
+    ($($arg:ident),*) => {
+        impl<P, F, $($arg,)*> Handler<P, ($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> Result<Response, HandlerError>,
+            $($arg: FromPayload<P>,)*
+        {
+            #[allow(non_snake_case, unused_variables)]
+            fn call(&self, payload: &P) -> Result<Response, HandlerError> {
+                $(let $arg = $arg::from_payload(payload)?;)*
+                self($($arg),*)
+            }
+        }
+    };
+}
+
+impl_handler!();
+impl_handler!(A1);
+impl_handler!(A1, A2);
+impl_handler!(A1, A2, A3);
+impl_handler!(A1, A2, A3, A4);
+impl_handler!(A1, A2, A3, A4, A5);
+impl_handler!(A1, A2, A3, A4, A5, A6);
+
+/// The id of the user that caused the interaction.
+pub struct UserId(pub Snowflake);
+
+/// The invoking user's selected language, e.g. `en-US`, falling back to the guild's preferred
+/// language, and then to `en-US`, if Discord didn't provide it.
+pub struct Locale(pub String);
+
+/// The application state of type `S` given to the runner with `Runner::state`. Extraction fails
+/// if the runner has no state of that type.
+pub struct State<S>(pub Arc<S>);
+
+impl<S> Deref for State<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+/// User options can be read as a `UserId`, e.g. in the fields of a `FromOptions` struct.
+impl FromOptionValue for UserId {
+    const TYPE: OptionType = OptionType::User;
+
+    fn from_option_value(value: &CommandOptionValue) -> Option<Self> {
+        Snowflake::from_option_value(value).map(UserId)
+    }
+}
+
+/// A command's options, read into a `T` with `FromOptions`.
+pub struct Options<T>(pub T);
+
+/// A type that can be built from an application command's options, for the `Options` extractor.
+/// With the `derive` feature, this can be derived for structs whose fields are named after the
+/// options, with `#[derive(FromOptions)]`.
+pub trait FromOptions: Sized {
+    fn from_options(ac: &ApplicationCommand) -> Result<Self, HandlerError>;
+}
+
+impl<T: FromOptions> FromPayload<ApplicationCommand> for Options<T> {
+    fn from_payload(ac: &ApplicationCommand) -> Result<Self, HandlerError> {
+        T::from_options(ac).map(Options)
+    }
+}
+
+/// The parts of the custom id or modal id captured by its `Router` pattern.
+impl FromPayload<MessageComponent> for Params {
+    fn from_payload(mc: &MessageComponent) -> Result<Self, HandlerError> {
+        Ok(mc.params.clone())
+    }
+}
+
+impl FromPayload<ModalSubmit> for Params {
+    fn from_payload(ms: &ModalSubmit) -> Result<Self, HandlerError> {
+        Ok(ms.params.clone())
+    }
+}
+
+macro_rules! impl_common_extractors {
+    ($($payload:ty),*) => {
+        $(
+            impl FromPayload<$payload> for UserId {
+                fn from_payload(payload: &$payload) -> Result<Self, HandlerError> {
+                    Ok(UserId(payload.user_id.clone()))
+                }
+            }
+
+            impl FromPayload<$payload> for Locale {
+                fn from_payload(payload: &$payload) -> Result<Self, HandlerError> {
+                    let locale = payload.locale.as_ref().or(payload.guild_locale.as_ref());
+                    Ok(Locale(locale.map_or("en-US", |l| l).to_string()))
+                }
+            }
+
+            impl<S: Any + Send + Sync> FromPayload<$payload> for State<S> {
+                fn from_payload(payload: &$payload) -> Result<Self, HandlerError> {
+                    match payload.state.get_arc() {
+                        Some(state) => Ok(State(state)),
+                        None => Err(HandlerError::new(&format!(
+                            "Runner has no state of type `{}`",
+                            type_name::<S>()
+                        ))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_common_extractors!(ApplicationCommand, MessageComponent, ModalSubmit);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discord_types::InteractionRequest;
+    use crate::Message;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    struct Repeat {
+        times: i64,
+    }
+
+    impl FromOptions for Repeat {
+        fn from_options(ac: &ApplicationCommand) -> Result<Self, HandlerError> {
+            Ok(Repeat {
+                times: ac.required_option("times")?,
+            })
+        }
+    }
+
+    fn command(options: serde_json::Value) -> ApplicationCommand {
+        let req = json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 2,
+            "user": { "id": "53908232506183680", "username": "mason" },
+            "data": { "id": "771825006014889984", "name": "repeat", "type": 1, "options": options },
+        });
+        ApplicationCommand::from(&InteractionRequest::from_json(&req.to_string()).unwrap())
+    }
+
+    fn ok() -> Result<Response, HandlerError> {
+        Ok(Response::Message(Message::new().text("Done")))
+    }
+
+    #[test]
+    fn failed_extraction_skips_the_handler() {
+        let calls = Cell::new(0);
+        let handler = |UserId(_): UserId, Options(repeat): Options<Repeat>| {
+            calls.set(calls.get() + repeat.times);
+            ok()
+        };
+
+        let err = Handler::call(&handler, &command(json!([]))).err().unwrap();
+        assert_eq!(err.message, "Missing option `times`");
+
+        let options = json!([{ "name": "times", "type": 3, "value": "twice" }]);
+        let err = Handler::call(&handler, &command(options)).err().unwrap();
+        assert_eq!(err.message, "Option `times` should have type Integer");
+        assert_eq!(calls.get(), 0);
+
+        let options = json!([{ "name": "times", "type": 4, "value": 2 }]);
+        assert!(Handler::call(&handler, &command(options)).is_ok());
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn missing_state() {
+        let handler = |State(count): State<u32>| {
+            assert_eq!(*count, 5);
+            ok()
+        };
+
+        let mut ac = command(json!([]));
+        let err = Handler::call(&handler, &ac).err().unwrap();
+        assert_eq!(err.message, "Runner has no state of type `u32`");
+
+        ac.state.set("wrong type");
+        assert!(Handler::call(&handler, &ac).is_err());
+
+        ac.state.set(5u32);
+        assert!(Handler::call(&handler, &ac).is_ok());
+    }
+
+    #[test]
+    fn locale_falls_back_to_en_us() {
+        let ac = command(json!([]));
+        assert_eq!(Locale::from_payload(&ac).unwrap().0, "en-US");
+    }
+}
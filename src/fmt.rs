@@ -0,0 +1,153 @@
+/*!
+ * Helpers for formatting message text with Discord's markdown, e.g. for mentioning the invoking
+ * user with `fmt::user(&ac.user_id)`, or for showing user input as it was typed with
+ * `fmt::escape(&text)`. Note that mentions only notify whoever they mention if the message's
+ * allowed mentions permit it; see `Message::allowed_mentions`.
+ */
+
+use super::snowflake::Snowflake;
+
+/// Mentions a user, e.g. `<@80351110224678912>`.
+pub fn user(id: &Snowflake) -> String {
+    format!("<@{}>", id)
+}
+
+/// Mentions a role, e.g. `<@&165511591545143296>`.
+pub fn role(id: &Snowflake) -> String {
+    format!("<@&{}>", id)
+}
+
+/// Mentions a channel, e.g. `<#103735883630395392>`.
+pub fn channel(id: &Snowflake) -> String {
+    format!("<#{}>", id)
+}
+
+/// How a timestamp is displayed. Discord renders timestamps in each user's own time zone and
+/// locale; the examples here are for `en-US`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimestampStyle {
+    /// e.g. `4:20 PM`
+    ShortTime,
+    /// e.g. `4:20:30 PM`
+    LongTime,
+    /// e.g. `10/16/2026`
+    ShortDate,
+    /// e.g. `October 16, 2026`
+    LongDate,
+    /// e.g. `October 16, 2026 4:20 PM`
+    ShortDateTime,
+    /// e.g. `Friday, October 16, 2026 4:20 PM`
+    LongDateTime,
+    /// e.g. `in 2 hours` or `3 days ago`, kept up to date as time passes.
+    Relative,
+}
+
+/// Displays a point in time, given as seconds since the Unix epoch, e.g. `<t:1792167630:R>`.
+pub fn timestamp(unix_seconds: i64, style: TimestampStyle) -> String {
+    let style = match style {
+        TimestampStyle::ShortTime => 't',
+        TimestampStyle::LongTime => 'T',
+        TimestampStyle::ShortDate => 'd',
+        TimestampStyle::LongDate => 'D',
+        TimestampStyle::ShortDateTime => 'f',
+        TimestampStyle::LongDateTime => 'F',
+        TimestampStyle::Relative => 'R',
+    };
+
+    format!("<t:{}:{}>", unix_seconds, style)
+}
+
+/// Formats text as inline code. Backticks in the text are kept, by fencing it with more backticks
+/// than it contains in a row.
+pub fn code(text: &str) -> String {
+    let fence = "`".repeat(longest_run(text, '`') + 1);
+
+    // Padding keeps a leading or trailing backtick from joining the fence.
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", fence, text, fence)
+    } else {
+        format!("{}{}{}", fence, text, fence)
+    }
+}
+
+/// Formats text as a code block, highlighted as the given language (e.g. `rust`), or not at all
+/// if `language` is empty. Discord has no way of escaping a fence inside a code block, so any
+/// "```" in the text is broken up with zero-width spaces.
+pub fn code_block(language: &str, text: &str) -> String {
+    let text = text.replace("```", "`\u{200B}`\u{200B}`");
+    format!("```{}\n{}\n```", language, text)
+}
+
+/// Hides text behind a spoiler, until the reader clicks on it.
+pub fn spoiler(text: &str) -> String {
+    format!("||{}||", text.replace("||", "|\u{200B}|"))
+}
+
+/// Escapes Discord's markdown in user-provided text, so that it's displayed as it was typed.
+/// Mentions in the text are escaped too, and so are displayed rather than rendered.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if "\\*_~`|><#-[]()@".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_markdown_and_mentions() {
+        assert_eq!(escape("plain text"), "plain text");
+        assert_eq!(escape("**bold** _it_"), r"\*\*bold\*\* \_it\_");
+        assert_eq!(escape("<@80351110224678912>"), r"\<\@80351110224678912\>");
+        assert_eq!(escape(r"C:\path"), r"C:\\path");
+        assert_eq!(escape("# heading\n- item"), "\\# heading\n\\- item");
+        assert_eq!(escape("[link](url)"), r"\[link\]\(url\)");
+        assert_eq!(escape("||~~`x`~~||"), r"\|\|\~\~\`x\`\~\~\|\|");
+        assert_eq!(escape("héllo ✨"), "héllo ✨");
+    }
+
+    #[test]
+    fn code_fences_outgrow_backticks_in_the_text() {
+        assert_eq!(code("x"), "`x`");
+        assert_eq!(code("a`b"), "``a`b``");
+        assert_eq!(code("a```b`c"), "````a```b`c````");
+        // Backticks at either end are padded, so they don't join the fence.
+        assert_eq!(code("`x"), "`` `x ``");
+        assert_eq!(code("x``"), "``` x`` ```");
+    }
+
+    #[test]
+    fn code_blocks_and_spoilers() {
+        assert_eq!(code_block("rust", "let x = 1;"), "```rust\nlet x = 1;\n```");
+        assert_eq!(code_block("", "```"), "```\n`\u{200B}`\u{200B}`\n```");
+        assert_eq!(spoiler("a||b"), "||a|\u{200B}|b||");
+    }
+
+    #[test]
+    fn mentions_and_timestamps() {
+        let id = Snowflake::from("80351110224678912");
+        assert_eq!(user(&id), "<@80351110224678912>");
+        assert_eq!(role(&id), "<@&80351110224678912>");
+        assert_eq!(channel(&id), "<#80351110224678912>");
+        assert_eq!(
+            timestamp(1792167630, TimestampStyle::Relative),
+            "<t:1792167630:R>"
+        );
+        assert_eq!(timestamp(0, TimestampStyle::LongDate), "<t:0:D>");
+    }
+}
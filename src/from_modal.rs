@@ -0,0 +1,39 @@
+use super::user_types::ModalSubmit;
+use std::error::Error;
+use std::fmt;
+
+/// A type that can be built from a submitted modal, with `ModalSubmit::parse`. With the `derive`
+/// feature, this can be derived for structs whose fields match the ids of the modal's inputs.
+pub trait FromModal: Sized {
+    fn from_modal(ms: &ModalSubmit) -> Result<Self, ModalError>;
+}
+
+/// The ways reading a submitted modal's inputs can fail.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ModalError {
+    /// The modal had no input with the given id.
+    Missing(String),
+    /// An input's value couldn't be parsed as the expected type.
+    Invalid {
+        id: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ModalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModalError::Missing(id) => write!(f, "modal has no input `{}`", id),
+            ModalError::Invalid { id, value, reason } => {
+                write!(
+                    f,
+                    "modal input `{}` has invalid value {:?}: {}",
+                    id, value, reason
+                )
+            }
+        }
+    }
+}
+
+impl Error for ModalError {}
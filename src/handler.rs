@@ -1,47 +1,245 @@
 use super::discord_types::{
-    InteractionCallbackType, InteractionRequest, InteractionResponse, InteractionType,
+    AllowedMentions, InteractionCallbackData, InteractionCallbackType, InteractionRequest,
+    InteractionResponse, InteractionType,
 };
-use super::user_types::{ApplicationCommand, MessageComponent, ModalSubmit, Response};
+use super::error::HandlerError;
+use super::message_flags;
+use super::middleware::{Middleware, Next};
+use super::state::State;
+use super::user_types::{
+    ApplicationCommand, AutocompleteRequest, Choice, MessageComponent, ModalSubmit, Response,
+};
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 
 /// General interaction handler type, to be implemented by your application. To implement this trait, you must at minimum be able to handle incoming application commands (slash commands). If your application involves buttons or modal inputs, you should implement the corresponding trait functions as well.
+///
+/// A `Router` implements this trait by dispatching to closures registered per command name and
+/// custom id, as an alternative to matching on them by hand.
+///
+/// Handlers that can't respond return a `HandlerError`; see there for how errors are reported to
+/// the user. By default, message components and modal submits are treated as unknown
+/// interactions.
 pub trait InteractionHandler {
     #[allow(unused)]
-    fn handle_application_command(ac: ApplicationCommand) -> Response;
+    fn handle_application_command(&self, ac: ApplicationCommand) -> Result<Response, HandlerError>;
 
     #[allow(unused)]
-    fn handle_message_component(mc: MessageComponent) -> Response {
-        todo!();
+    fn handle_message_component(&self, mc: MessageComponent) -> Result<Response, HandlerError> {
+        Err(HandlerError::unknown())
     }
 
     #[allow(unused)]
-    fn handle_modal_submit(ms: ModalSubmit) -> Response {
-        todo!();
+    fn handle_modal_submit(&self, ms: ModalSubmit) -> Result<Response, HandlerError> {
+        Err(HandlerError::unknown())
+    }
+
+    /// Suggests choices for a command option with autocomplete enabled, as the user types it.
+    /// Suggests nothing by default.
+    #[allow(unused)]
+    fn handle_autocomplete(&self, ac: AutocompleteRequest) -> Vec<Choice> {
+        Vec::new()
     }
 }
 
-pub fn handle_interaction<T>(req: &InteractionRequest) -> InteractionResponse
+/// The text of the reply sent when the reply to an error fails too.
+const LAST_RESORT_MESSAGE: &str = "Something went wrong.";
+
+/// Handles an interaction, returning the response to send back, or the error if the handler
+/// failed with a `HandlerError::infrastructure`.
+pub fn process_interaction<T>(
+    handler: &T,
+    req: &InteractionRequest,
+    middleware: &[Arc<dyn Middleware + Send + Sync>],
+    on_error: &dyn Fn(&InteractionRequest, HandlerError) -> Response,
+    state: &State,
+) -> Result<InteractionResponse, HandlerError>
 where
     T: InteractionHandler,
 {
-    match req.r#type {
-        InteractionType::Ping => InteractionResponse {
+    if req.r#type == InteractionType::Ping {
+        return Ok(InteractionResponse {
             r#type: InteractionCallbackType::Pong,
             data: None,
-        },
+            files: Vec::new(),
+            raw: None,
+            followup: None,
+        });
+    }
 
-        InteractionType::ApplicationCommand => match T::handle_application_command(req.into()) {
-            Response::Message(m) => m.into(),
-            Response::Modal(m) => m.into(),
-        },
+    // Infrastructure errors skip the reply, and are set aside until the middleware has run, so
+    // that middleware doesn't need to handle them.
+    let infrastructure_error = RefCell::new(None);
 
-        InteractionType::MessageComponent => match T::handle_message_component(req.into()) {
-            Response::Message(m) => m.into(),
-            Response::Modal(m) => m.into(),
-        },
+    let endpoint = |req: &InteractionRequest| match dispatch(handler, req, state) {
+        Ok(res) => res,
+        Err(err) if err.infrastructure => {
+            let res = failure_response(req, HandlerError::new(&err.message), on_error);
+            *infrastructure_error.borrow_mut() = Some(err);
+            res
+        }
+        Err(err) => on_error(req, err),
+    };
 
-        InteractionType::ModalSubmit => match T::handle_modal_submit(req.into()) {
-            Response::Message(m) => m.into(),
-            Response::Modal(_) => panic!("Modal cannot result in another modal!"),
+    // Panics in the handler or middleware, and responses that Discord would reject, are reported
+    // like any other error, so that the user still gets a reply.
+    let res = catch_panic(|| validate(req, Next::new(middleware, &endpoint).run(req)));
+
+    if let Some(err) = infrastructure_error.into_inner() {
+        return Err(err);
+    }
+
+    Ok(res
+        .or_else(|err| catch_panic(|| validate(req, failure_response(req, err, on_error))))
+        .unwrap_or_else(|err| {
+            // The reply to the error failed too, e.g. in a custom `on_unknown` fallback, so no
+            // more user code is run.
+            tracing::error!({ %err }, "Error reply failed");
+            last_resort_response(req)
+        }))
+}
+
+/// The response sent when even the reply to an error couldn't be produced: a fixed ephemeral
+/// message, or no suggestions for autocomplete. It's built directly, so it can't fail.
+fn last_resort_response(req: &InteractionRequest) -> InteractionResponse {
+    let data = if req.r#type == InteractionType::ApplicationCommandAutocomplete {
+        InteractionCallbackData {
+            choices: Some(Vec::new()),
+            ..Default::default()
+        }
+    } else {
+        InteractionCallbackData {
+            content: Some(LAST_RESORT_MESSAGE.to_string()),
+            flags: Some(message_flags::EPHEMERAL),
+            allowed_mentions: Some(AllowedMentions {
+                parse: Vec::new(),
+                users: Vec::new(),
+                roles: Vec::new(),
+                replied_user: None,
+            }),
+            ..Default::default()
+        }
+    };
+
+    InteractionResponse {
+        r#type: if req.r#type == InteractionType::ApplicationCommandAutocomplete {
+            InteractionCallbackType::ApplicationCommandAutocompleteResult
+        } else {
+            InteractionCallbackType::ChannelMessageWithSource
         },
+        data: Some(data),
+        files: Vec::new(),
+        raw: None,
+        followup: None,
+    }
+}
+
+/// Runs the given function, turning a panic into an error.
+fn catch_panic<F>(f: F) -> Result<InteractionResponse, HandlerError>
+where
+    F: FnOnce() -> Result<InteractionResponse, HandlerError>,
+{
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        Err(HandlerError::new(&format!(
+            "Handler panicked: {}",
+            panic_message(&*payload)
+        )))
+    })
+}
+
+/// Converts a response, failing if it can't be sent in reply to the interaction, or if Discord
+/// would reject it.
+fn validate(req: &InteractionRequest, res: Response) -> Result<InteractionResponse, HandlerError> {
+    let invalid = match (&req.r#type, &res) {
+        (InteractionType::ModalSubmit, Response::Modal(_)) => {
+            Some("Modal cannot result in another modal!")
+        }
+        (InteractionType::ApplicationCommand, Response::DeferredUpdate) => {
+            Some("Application command cannot result in a deferred update!")
+        }
+        #[cfg(feature = "download")]
+        (InteractionType::ApplicationCommand, Response::UpdateWithFollowup { .. }) => {
+            Some("Application command cannot result in an update!")
+        }
+        (InteractionType::ModalSubmit, Response::LaunchActivity) => {
+            Some("Modal cannot result in launching an activity!")
+        }
+        (
+            InteractionType::ApplicationCommandAutocomplete,
+            Response::Autocomplete(_) | Response::Raw(_),
+        ) => None,
+        (InteractionType::ApplicationCommandAutocomplete, _) => {
+            Some("Autocomplete interactions can only result in autocomplete choices!")
+        }
+        (_, Response::Autocomplete(_)) => {
+            Some("Only autocomplete interactions can result in autocomplete choices!")
+        }
+        _ => None,
+    };
+
+    match invalid {
+        Some(message) => Err(HandlerError::new(message)),
+        None => res.try_into(),
+    }
+}
+
+/// The response sent in place of one that couldn't be produced. Autocomplete errors are only
+/// logged, since the user never sees a reply to them, and suggest nothing.
+fn failure_response(
+    req: &InteractionRequest,
+    err: HandlerError,
+    on_error: &dyn Fn(&InteractionRequest, HandlerError) -> Response,
+) -> Response {
+    if req.r#type == InteractionType::ApplicationCommandAutocomplete {
+        tracing::error!({ %err }, "Autocomplete handler failed");
+        Response::Autocomplete(Vec::new())
+    } else {
+        on_error(req, err)
+    }
+}
+
+fn dispatch<T>(
+    handler: &T,
+    req: &InteractionRequest,
+    state: &State,
+) -> Result<Response, HandlerError>
+where
+    T: InteractionHandler,
+{
+    match req.r#type {
+        InteractionType::Ping => unreachable!(),
+        InteractionType::ApplicationCommand => {
+            let mut ac = ApplicationCommand::from(req);
+            ac.state = state.clone();
+            handler.handle_application_command(ac)
+        }
+        InteractionType::MessageComponent => {
+            let mut mc = MessageComponent::from(req);
+            mc.state = state.clone();
+            handler.handle_message_component(mc)
+        }
+        InteractionType::ModalSubmit => {
+            let mut ms = ModalSubmit::from(req);
+            ms.state = state.clone();
+            handler.handle_modal_submit(ms)
+        }
+        InteractionType::ApplicationCommandAutocomplete => {
+            let mut ac = AutocompleteRequest::from(req);
+            ac.command.state = state.clone();
+            Ok(Response::Autocomplete(handler.handle_autocomplete(ac)))
+        }
+    }
+}
+
+/// The message a panic was raised with, if it was given one.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
     }
 }
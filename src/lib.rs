@@ -4,11 +4,60 @@
 
 //! Much of a Discord bot's behavior can be described using an request/response model, which a slash command, button press, or other user interaction is sent to a backend, and the backend returns a single response. This request/response is called an *interaction* by Discord, which is specified in detail in the Discord developer docs. This library wraps these request and response types, handling authentication, exposing application-friendly types, and integrating these types with AWS Lambda.
 
+#[cfg(feature = "actix")]
+pub mod actix;
 mod auth;
+#[cfg(feature = "axum")]
+pub mod axum;
+mod command;
 mod discord_types;
+mod error;
+pub mod extract;
+pub mod fmt;
+mod from_modal;
 mod handler;
+pub mod message_flags;
+mod middleware;
+pub mod permissions;
+#[cfg(feature = "download")]
+mod rest;
+mod router;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(feature = "shuttle")]
+pub mod shuttle;
+mod snowflake;
+mod state;
+#[cfg(feature = "tower")]
+pub mod tower;
 mod user_types;
+#[cfg(feature = "warp")]
+pub mod warp;
+#[cfg(feature = "workers")]
+pub mod workers;
 
-pub use auth::run;
+pub use auth::{
+    handle_interaction, handle_interaction_with_key, Headers, HttpResponseParts, PublicKey, Runner,
+};
+#[cfg(feature = "lambda")]
+pub use auth::{handle_one, run, run_handler, run_with_state, PayloadFormat};
+#[doc(hidden)]
+#[cfg(not(target_arch = "wasm32"))]
+pub use command::block_on;
+pub use command::{CommandDefinition, FromOptionValue, OptionDefinition, OptionType, SlashCommand};
+pub use discord_types::InteractionRequest;
+#[cfg(feature = "download")]
+pub use error::ApiError;
+pub use error::{HandlerError, InvalidPublicKey};
+pub use from_modal::{FromModal, ModalError};
 pub use handler::InteractionHandler;
+pub use middleware::{Middleware, Next};
+pub use router::{Params, Router};
+#[cfg(feature = "server")]
+pub use server::run_server;
+pub use snowflake::Snowflake;
+pub use state::State;
 pub use user_types::*;
+
+#[cfg(feature = "derive")]
+pub use discord_interaction_derive::{slash_command, FromModal, FromOptions};
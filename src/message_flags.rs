@@ -0,0 +1,21 @@
+/*!
+ * Discord message flags, for combining in `Message::flags` and for checking the flags bitfield
+ * exposed on source messages (`SourceMessage::flags`). For example, an application can check
+ * whether a message is ephemeral with `mc.source.flags & message_flags::EPHEMERAL != 0`. Only
+ * `SUPPRESS_EMBEDS`, `EPHEMERAL`, `SUPPRESS_NOTIFICATIONS` and `IS_COMPONENTS_V2` can be set on
+ * interaction responses.
+ */
+
+pub const CROSSPOSTED: u64 = 1 << 0;
+pub const IS_CROSSPOST: u64 = 1 << 1;
+pub const SUPPRESS_EMBEDS: u64 = 1 << 2;
+pub const SOURCE_MESSAGE_DELETED: u64 = 1 << 3;
+pub const URGENT: u64 = 1 << 4;
+pub const HAS_THREAD: u64 = 1 << 5;
+pub const EPHEMERAL: u64 = 1 << 6;
+pub const LOADING: u64 = 1 << 7;
+pub const FAILED_TO_MENTION_SOME_ROLES_IN_THREAD: u64 = 1 << 8;
+pub const SUPPRESS_NOTIFICATIONS: u64 = 1 << 12;
+pub const IS_VOICE_MESSAGE: u64 = 1 << 13;
+pub const HAS_SNAPSHOT: u64 = 1 << 14;
+pub const IS_COMPONENTS_V2: u64 = 1 << 15;
@@ -0,0 +1,70 @@
+use super::discord_types::InteractionRequest;
+use super::user_types::Response;
+use std::sync::Arc;
+
+/// Cross-cutting logic that wraps the dispatch of interactions to an `InteractionHandler`, such as
+/// authorization checks, logging, or feature flags. Middleware is added to a `Runner`, and applies
+/// to every interaction other than Discord's pings.
+///
+/// Middleware runs in the order it was added to the runner. Each middleware's `before` is called
+/// before the handler, and the first one to return a response short-circuits dispatch: neither
+/// the handler nor any later middleware's `before` is called. Each middleware's `after` is then
+/// called in reverse order with the resulting response, skipping the middleware that
+/// short-circuited and any that come after it.
+///
+/// Middleware that needs to wrap the rest of the chain as a whole, e.g. to time it or to skip it
+/// conditionally after inspecting the response, can instead override `wrap`, which replaces
+/// `before` and `after`.
+pub trait Middleware {
+    /// Handles the interaction by calling `next.run`, which runs the rest of the middleware chain
+    /// and then the handler, and returns the response to use. By default, this calls `before`,
+    /// and unless it short-circuits, runs the rest of the chain and passes its response through
+    /// `after`.
+    fn wrap(&self, req: &InteractionRequest, next: Next<'_>) -> Response {
+        match self.before(req) {
+            Some(res) => res,
+            None => self.after(req, next.run(req)),
+        }
+    }
+
+    /// Called before the interaction is handled. Returning `Some` skips the handler, and uses the
+    /// returned response instead.
+    #[allow(unused)]
+    fn before(&self, req: &InteractionRequest) -> Option<Response> {
+        None
+    }
+
+    /// Called after the interaction is handled, with the response produced by the handler (or by
+    /// a short-circuiting middleware later in the chain). Returns the response to use in its
+    /// place.
+    #[allow(unused)]
+    fn after(&self, req: &InteractionRequest, res: Response) -> Response {
+        res
+    }
+}
+
+/// The rest of a middleware chain, along with the handler at its end. See `Middleware::wrap`.
+pub struct Next<'a> {
+    middleware: &'a [Arc<dyn Middleware + Send + Sync>],
+    endpoint: &'a dyn Fn(&InteractionRequest) -> Response,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        middleware: &'a [Arc<dyn Middleware + Send + Sync>],
+        endpoint: &'a dyn Fn(&InteractionRequest) -> Response,
+    ) -> Self {
+        Next {
+            middleware,
+            endpoint,
+        }
+    }
+
+    /// Runs the rest of the chain, returning the resulting response.
+    pub fn run(self, req: &InteractionRequest) -> Response {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.wrap(req, Next::new(rest, self.endpoint)),
+            None => (self.endpoint)(req),
+        }
+    }
+}
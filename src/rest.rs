@@ -0,0 +1,73 @@
+use super::auth::multipart;
+use super::discord_types::{InteractionResponse, User};
+use super::error::{ApiError, HandlerError};
+use super::snowflake::Snowflake;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+
+/// The base URL of Discord's HTTP API.
+const API_URL: &str = "https://discord.com/api/v10";
+
+/// Responds to an interaction through its callback endpoint, rather than in answer to Discord's
+/// request, with a response that has already been encoded.
+pub(crate) async fn create_response(
+    interaction_id: &Snowflake,
+    token: &str,
+    content_type: String,
+    body: Vec<u8>,
+) -> Result<(), HandlerError> {
+    let url = format!(
+        "{}/interactions/{}/{}/callback",
+        API_URL, interaction_id, token
+    );
+    post(url, content_type, body).await
+}
+
+/// Creates a followup message for an interaction through its webhook, from the data and files of
+/// the given response.
+pub(crate) async fn create_followup(
+    application_id: &Snowflake,
+    token: &str,
+    res: &InteractionResponse,
+) -> Result<(), HandlerError> {
+    let url = format!("{}/webhooks/{}/{}", API_URL, application_id, token);
+    let payload_json = serde_json::to_string(&res.data)?;
+
+    tracing::info!({ %payload_json }, "Followup JSON");
+
+    let (content_type, body) = if res.files.is_empty() {
+        ("application/json".to_string(), payload_json.into_bytes())
+    } else {
+        multipart(&payload_json, &res.files)
+    };
+
+    post(url, content_type, body).await
+}
+
+/// Gets the user with the given id, on behalf of the bot with the given token.
+pub(crate) async fn get_user(bot_token: &str, user_id: &Snowflake) -> Result<User, ApiError> {
+    let url = format!("{}/users/{}", API_URL, user_id);
+
+    let user_json = reqwest::Client::new()
+        .get(url)
+        .header(AUTHORIZATION, format!("Bot {}", bot_token))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    Ok(serde_json::from_str(&user_json)?)
+}
+
+/// Posts the given body, failing if Discord answers with an error status.
+async fn post(url: String, content_type: String, body: Vec<u8>) -> Result<(), HandlerError> {
+    reqwest::Client::new()
+        .post(url)
+        .header(CONTENT_TYPE, content_type)
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
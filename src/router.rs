@@ -0,0 +1,507 @@
+use super::command::{CommandDefinition, SlashCommand};
+use super::error::HandlerError;
+use super::extract::Handler;
+use super::handler::InteractionHandler;
+use super::user_types::{
+    ApplicationCommand, AutocompleteRequest, Choice, MessageComponent, ModalSubmit, Response,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+type Route<P> = Box<dyn Fn(P) -> Result<Response, HandlerError> + Send + Sync>;
+type AutocompleteRoute = Box<dyn Fn(AutocompleteRequest) -> Vec<Choice> + Send + Sync>;
+
+/// An `InteractionHandler` that dispatches each interaction to a closure registered for its
+/// command name, custom id or modal id. Interactions without a registered closure are treated as
+/// unknown (see `Runner::on_unknown`).
+///
+/// Custom ids and modal ids can be routed by pattern, with `{name}` placeholders that capture
+/// parts of the id, e.g. `vote:{poll}:{choice}`. The captured parts are passed to the handler in
+/// the payload's `params`. Ids that match exactly take precedence over patterns, and patterns are
+/// tried in the order they were added.
+///
+/// Each command name, custom id, modal id and pattern can be routed only once: adding a second
+/// route for one panics, as the router is being built, rather than one route silently shadowing
+/// the other.
+///
+/// Routers can be built up across modules, each contributing its own routes, and combined with
+/// `merge`. Run a router with `run_handler` or `Runner::with_handler`.
+#[derive(Default)]
+pub struct Router {
+    commands: HashMap<String, Route<ApplicationCommand>>,
+    components: Routes<MessageComponent>,
+    modals: Routes<ModalSubmit>,
+    autocompletes: HashMap<String, AutocompleteRoute>,
+    definitions: Vec<CommandDefinition>,
+}
+
+/// Routes for custom ids, either exact or by pattern.
+struct Routes<P> {
+    exact: HashMap<String, Route<P>>,
+    patterns: Vec<(Pattern, Route<P>)>,
+}
+
+impl<P> Default for Routes<P> {
+    fn default() -> Self {
+        Routes {
+            exact: HashMap::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl<P> Routes<P> {
+    fn insert(&mut self, id: &str, route: Route<P>, kind: &str) {
+        let pattern = Pattern::parse(id);
+        if pattern.0.iter().any(|part| matches!(part, Part::Param(_))) {
+            self.insert_pattern(pattern, route, kind);
+        } else {
+            insert_route(&mut self.exact, id.to_string(), route, kind);
+        }
+    }
+
+    fn insert_pattern(&mut self, pattern: Pattern, route: Route<P>, kind: &str) {
+        if self
+            .patterns
+            .iter()
+            .any(|(existing, _)| *existing == pattern)
+        {
+            panic!("Duplicate {} route `{}`!", kind, pattern);
+        }
+        self.patterns.push((pattern, route));
+    }
+
+    /// Finds the route for an id, along with the parameters captured from it.
+    fn find(&self, id: &str) -> Option<(&Route<P>, Params)> {
+        if let Some(route) = self.exact.get(id) {
+            return Some((route, Params::default()));
+        }
+
+        self.patterns
+            .iter()
+            .find_map(|(pattern, route)| Some((route, pattern.captures(id)?)))
+    }
+
+    fn merge(&mut self, other: Routes<P>, kind: &str) {
+        merge_routes(&mut self.exact, other.exact, kind);
+        for (pattern, route) in other.patterns {
+            self.insert_pattern(pattern, route, kind);
+        }
+    }
+}
+
+/// A custom id pattern, as a sequence of literal text and `{name}` placeholders.
+#[derive(PartialEq)]
+struct Pattern(Vec<Part>);
+
+#[derive(PartialEq)]
+enum Part {
+    Literal(String),
+    Param(String),
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut rest = pattern;
+
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => panic!("Unclosed placeholder in pattern `{}`!", pattern),
+            };
+
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            if matches!(parts.last(), Some(Part::Param(_))) {
+                panic!(
+                    "Placeholders in pattern `{}` must be separated by text!",
+                    pattern
+                );
+            }
+            parts.push(Part::Param(rest[start + 1..end].to_string()));
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+
+        Pattern(parts)
+    }
+
+    /// Matches an id against the pattern, capturing the text matched by each placeholder. Each
+    /// placeholder matches at least one character, up to the first occurrence of the text after
+    /// it.
+    fn captures(&self, id: &str) -> Option<Params> {
+        let mut params = HashMap::new();
+        let mut rest = id;
+
+        for (i, part) in self.0.iter().enumerate() {
+            match part {
+                Part::Literal(text) => rest = rest.strip_prefix(text.as_str())?,
+                Part::Param(name) => {
+                    let end = match self.0.get(i + 1) {
+                        Some(Part::Literal(next)) => {
+                            let first = rest.chars().next()?.len_utf8();
+                            rest[first..].find(next.as_str())? + first
+                        }
+                        _ => rest.len(),
+                    };
+                    if end == 0 {
+                        return None;
+                    }
+                    params.insert(name.clone(), rest[..end].to_string());
+                    rest = &rest[end..];
+                }
+            }
+        }
+
+        rest.is_empty().then_some(Params(params))
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for part in &self.0 {
+            match part {
+                Part::Literal(text) => write!(f, "{}", text)?,
+                Part::Param(name) => write!(f, "{{{}}}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The parts of a custom id captured by the placeholders of the `Router` pattern it matched.
+#[derive(Clone, Default, Debug)]
+pub struct Params(HashMap<String, String>);
+
+impl Params {
+    /// Parses the text captured by the placeholder with the given name. Panics if the pattern has
+    /// no such placeholder.
+    pub fn get<T>(&self, name: &str) -> Result<T, HandlerError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = match self.0.get(name) {
+            Some(value) => value,
+            None => panic!("Pattern has no placeholder `{}`!", name),
+        };
+
+        value.parse().map_err(|err: T::Err| {
+            HandlerError::new(&format!(
+                "Invalid value {:?} for parameter `{}`: {}",
+                value, name, err
+            ))
+        })
+    }
+}
+
+impl Router {
+    /// Creates a new router, without any routes.
+    pub fn new() -> Self {
+        Router::default()
+    }
+
+    /// Routes the application command with the given name to `handler`.
+    pub fn command<F>(mut self, name: &str, handler: F) -> Self
+    where
+        F: Fn(ApplicationCommand) -> Result<Response, HandlerError> + Send + Sync + 'static,
+    {
+        insert_route(
+            &mut self.commands,
+            name.to_string(),
+            Box::new(handler),
+            "command",
+        );
+        self
+    }
+
+    /// Routes the message component with the given custom id, or custom ids matching the given
+    /// pattern, to `handler`.
+    pub fn component<F>(mut self, id: &str, handler: F) -> Self
+    where
+        F: Fn(MessageComponent) -> Result<Response, HandlerError> + Send + Sync + 'static,
+    {
+        self.components.insert(id, Box::new(handler), "component");
+        self
+    }
+
+    /// Routes the submits of the modal with the given id, or ids matching the given pattern, to
+    /// `handler`.
+    pub fn modal<F>(mut self, id: &str, handler: F) -> Self
+    where
+        F: Fn(ModalSubmit) -> Result<Response, HandlerError> + Send + Sync + 'static,
+    {
+        self.modals.insert(id, Box::new(handler), "modal");
+        self
+    }
+
+    /// Routes the application command with the given name to a function whose arguments are
+    /// extractors (see the `extract` module).
+    pub fn command_handler<H, Args>(self, name: &str, handler: H) -> Self
+    where
+        H: Handler<ApplicationCommand, Args> + Send + Sync + 'static,
+    {
+        self.command(name, move |ac| handler.call(&ac))
+    }
+
+    /// Routes a command generated by `#[slash_command]` to its handler, and adds its definition to
+    /// the router's `definitions`.
+    pub fn slash_command<C>(mut self, command: C) -> Self
+    where
+        C: SlashCommand + Send + Sync + 'static,
+    {
+        let definition = command.definition();
+        let name = definition.name.clone();
+        self.definitions.push(definition);
+        self.command(&name, move |ac| command.handle(ac))
+    }
+
+    /// Routes the message component with the given custom id, or custom ids matching the given
+    /// pattern, to a function whose arguments are extractors.
+    pub fn component_handler<H, Args>(self, id: &str, handler: H) -> Self
+    where
+        H: Handler<MessageComponent, Args> + Send + Sync + 'static,
+    {
+        self.component(id, move |mc| handler.call(&mc))
+    }
+
+    /// Routes the submits of the modal with the given id, or ids matching the given pattern, to a
+    /// function whose arguments are extractors.
+    pub fn modal_handler<H, Args>(self, id: &str, handler: H) -> Self
+    where
+        H: Handler<ModalSubmit, Args> + Send + Sync + 'static,
+    {
+        self.modal(id, move |ms| handler.call(&ms))
+    }
+
+    /// Routes autocomplete requests for the application command with the given name to `handler`.
+    pub fn autocomplete<F>(mut self, name: &str, handler: F) -> Self
+    where
+        F: Fn(AutocompleteRequest) -> Vec<Choice> + Send + Sync + 'static,
+    {
+        insert_route(
+            &mut self.autocompletes,
+            name.to_string(),
+            Box::new(handler),
+            "autocomplete",
+        );
+        self
+    }
+
+    /// Adds all of another router's routes to this one, with its patterns tried after this
+    /// router's. Panics if both routers have a route for the same command name, custom id, modal
+    /// id or pattern.
+    pub fn merge(mut self, other: Router) -> Self {
+        merge_routes(&mut self.commands, other.commands, "command");
+        self.components.merge(other.components, "component");
+        self.modals.merge(other.modals, "modal");
+        merge_routes(&mut self.autocompletes, other.autocompletes, "autocomplete");
+        self.definitions.extend(other.definitions);
+        self
+    }
+
+    /// The definitions of the commands added with `slash_command`, for registering them with
+    /// Discord.
+    pub fn definitions(&self) -> &[CommandDefinition] {
+        &self.definitions
+    }
+}
+
+fn merge_routes<R>(routes: &mut HashMap<String, R>, other: HashMap<String, R>, kind: &str) {
+    for (key, route) in other {
+        insert_route(routes, key, route, kind);
+    }
+}
+
+/// Adds a route, panicking if there's already one for the same key.
+fn insert_route<R>(routes: &mut HashMap<String, R>, key: String, route: R, kind: &str) {
+    if routes.contains_key(&key) {
+        panic!("Duplicate {} route `{}`!", kind, key);
+    }
+    routes.insert(key, route);
+}
+
+impl InteractionHandler for Router {
+    fn handle_application_command(&self, ac: ApplicationCommand) -> Result<Response, HandlerError> {
+        match self.commands.get(&ac.command_name) {
+            Some(handler) => handler(ac),
+            None => Err(HandlerError::unknown()),
+        }
+    }
+
+    fn handle_message_component(&self, mut mc: MessageComponent) -> Result<Response, HandlerError> {
+        match self.components.find(&mc.id) {
+            Some((handler, params)) => {
+                mc.params = params;
+                handler(mc)
+            }
+            None => Err(HandlerError::unknown()),
+        }
+    }
+
+    fn handle_modal_submit(&self, mut ms: ModalSubmit) -> Result<Response, HandlerError> {
+        match self.modals.find(&ms.id) {
+            Some((handler, params)) => {
+                ms.params = params;
+                handler(ms)
+            }
+            None => Err(HandlerError::unknown()),
+        }
+    }
+
+    fn handle_autocomplete(&self, ac: AutocompleteRequest) -> Vec<Choice> {
+        match self.autocompletes.get(&ac.command.command_name) {
+            Some(handler) => handler(ac),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown(_: MessageComponent) -> Result<Response, HandlerError> {
+        Err(HandlerError::unknown())
+    }
+
+    fn capture(pattern: &str, id: &str) -> Option<Vec<(String, String)>> {
+        let mut params: Vec<_> = Pattern::parse(pattern)
+            .captures(id)?
+            .0
+            .into_iter()
+            .collect();
+        params.sort();
+        Some(params)
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> Option<Vec<(String, String)>> {
+        Some(
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn pattern_captures() {
+        let pattern = "vote:{poll}:{choice}";
+        assert_eq!(
+            capture(pattern, "vote:12:yes"),
+            params(&[("choice", "yes"), ("poll", "12")])
+        );
+        // The last placeholder takes the rest of the id, separators included.
+        assert_eq!(
+            capture(pattern, "vote:12:yes:no"),
+            params(&[("choice", "yes:no"), ("poll", "12")])
+        );
+        assert_eq!(capture(pattern, "votes:12:yes"), None);
+        assert_eq!(capture(pattern, "vote:12"), None);
+        assert_eq!(capture("{id}", "anything"), params(&[("id", "anything")]));
+        assert_eq!(capture("edit", "edit"), params(&[]));
+    }
+
+    #[test]
+    fn placeholders_capture_at_least_one_character() {
+        let pattern = "vote:{poll}:{choice}";
+        assert_eq!(capture(pattern, "vote::yes"), None);
+        assert_eq!(capture(pattern, "vote:12:"), None);
+        // A capture may start with the text that ends it, since it must be at least one
+        // character long.
+        assert_eq!(
+            capture(pattern, "vote:::yes"),
+            params(&[("choice", "yes"), ("poll", ":")])
+        );
+    }
+
+    #[test]
+    fn adjacent_literals() {
+        // A placeholder ends at the first occurrence of the text after it.
+        assert_eq!(
+            capture("{name}.json", "config.json"),
+            params(&[("name", "config")])
+        );
+        assert_eq!(
+            capture("{name}.json", "config.old.json"),
+            params(&[("name", "config.old")])
+        );
+        assert_eq!(
+            capture("{a}-{b}", "x-y-z"),
+            params(&[("a", "x"), ("b", "y-z")])
+        );
+        assert_eq!(
+            capture("a{x}ab{y}b", "aaabbb"),
+            params(&[("x", "a"), ("y", "b")])
+        );
+    }
+
+    #[test]
+    fn multibyte_captures() {
+        assert_eq!(
+            capture("vote:{poll}:{choice}", "vote:🍕:é"),
+            params(&[("choice", "é"), ("poll", "🍕")])
+        );
+        assert_eq!(
+            capture("→{a}→{b}", "→ü→→"),
+            params(&[("a", "ü"), ("b", "→")])
+        );
+        assert_eq!(capture("→{a}→{b}", "→→"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Placeholders in pattern `{a}{b}` must be separated by text!")]
+    fn adjacent_placeholders() {
+        Pattern::parse("{a}{b}");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unclosed placeholder in pattern `vote:{poll`!")]
+    fn unclosed_placeholder() {
+        Pattern::parse("vote:{poll");
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate component route `confirm`!")]
+    fn duplicate_exact_route() {
+        Router::new()
+            .component("confirm", unknown)
+            .component("confirm", unknown);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate component route `vote:{poll}`!")]
+    fn duplicate_pattern_route() {
+        Router::new()
+            .component("vote:{poll}", unknown)
+            .component("vote:{poll}", unknown);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate component route `vote:{poll}`!")]
+    fn duplicate_merged_pattern_route() {
+        Router::new()
+            .component("vote:{poll}", unknown)
+            .merge(Router::new().component("vote:{poll}", unknown));
+    }
+
+    #[test]
+    fn distinct_routes_merge() {
+        let router = Router::new()
+            .component("confirm", unknown)
+            .component("vote:{poll}", unknown)
+            .merge(
+                Router::new()
+                    .component("cancel", unknown)
+                    .component("vote:{poll}:{choice}", unknown),
+            );
+        assert_eq!(router.components.exact.len(), 2);
+        assert_eq!(router.components.patterns.len(), 2);
+    }
+}
@@ -0,0 +1,40 @@
+use super::auth::Runner;
+use super::handler::InteractionHandler;
+use std::io;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+/// Serves interactions over HTTP at the given address, e.g. `0.0.0.0:8080`, for deploying to a VPS
+/// or container instead of AWS Lambda. Interactions are accepted as POST requests to any path, so
+/// the endpoint URL registered with Discord only needs to reach the server. Runs until the server
+/// fails, or fails immediately with an `InvalidInput` error if the public key isn't valid.
+///
+/// Requires the `server` feature.
+pub async fn run_server<A, T>(addr: A, public_key: &str, handler: T) -> io::Result<()>
+where
+    A: ToSocketAddrs,
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    Runner::try_with_handler(public_key, handler)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?
+        .run_server(addr)
+        .await
+}
+
+impl<T> Runner<T>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    /// Serves interactions over HTTP at the given address, as with `run_server`.
+    ///
+    /// Requires the `server` feature.
+    pub async fn run_server<A>(self, addr: A) -> io::Result<()>
+    where
+        A: ToSocketAddrs,
+    {
+        let app = ::axum::Router::new().fallback_service(crate::axum::runner_route(self));
+        let listener = TcpListener::bind(addr).await?;
+
+        tracing::info!(addr = ?listener.local_addr()?, "Serving interactions");
+        ::axum::serve(listener, app).await
+    }
+}
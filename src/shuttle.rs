@@ -0,0 +1,58 @@
+/*!
+ * Deploying interactions to [Shuttle](https://shuttle.rs), for bots that don't need AWS. Return a
+ * `ShuttleService` from the Shuttle entry point, and Shuttle serves it:
+ *
+ * ```ignore
+ * #[shuttle_runtime::main]
+ * async fn main(
+ *     #[shuttle_runtime::Secrets] secrets: shuttle_runtime::SecretStore,
+ * ) -> ShuttleInteractions<MyHandler> {
+ *     let public_key = secrets.get("DISCORD_PUBLIC_KEY").unwrap();
+ *     Ok(ShuttleService::new(&public_key, MyHandler))
+ * }
+ * ```
+ *
+ * Interactions are served as with `run_server`, so they're accepted at any path.
+ *
+ * Requires the `shuttle` feature.
+ */
+
+use super::auth::Runner;
+use super::handler::InteractionHandler;
+use std::net::SocketAddr;
+
+/// The return type of a Shuttle entry point that serves interactions.
+pub type ShuttleInteractions<T> = Result<ShuttleService<T>, shuttle_runtime::Error>;
+
+/// A Shuttle service that verifies and handles interactions with a runner.
+pub struct ShuttleService<T> {
+    runner: Runner<T>,
+}
+
+impl<T> ShuttleService<T>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    /// Creates a service that handles interactions with the given handler, verifying them with the
+    /// given application public key.
+    pub fn new(public_key: &str, handler: T) -> Self {
+        ShuttleService::with_runner(Runner::with_handler(public_key, handler))
+    }
+
+    /// Creates a service that handles interactions with a configured runner, e.g. one with
+    /// middleware or application state.
+    pub fn with_runner(runner: Runner<T>) -> Self {
+        ShuttleService { runner }
+    }
+}
+
+#[shuttle_runtime::async_trait]
+impl<T> shuttle_runtime::Service for ShuttleService<T>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    async fn bind(self, addr: SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        self.runner.run_server(addr).await?;
+        Ok(())
+    }
+}
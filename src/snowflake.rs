@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Milliseconds between the Unix epoch and the Discord epoch (the first second of 2015).
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// A Discord id, such as a user, guild, channel or message id. Snowflakes are unique across
+/// Discord, and embed the time at which the identified object was created.
+///
+/// Snowflakes are kept in the decimal string form that Discord sends them in, and compare with
+/// each other numerically, so that ordering snowflakes orders the identified objects by creation
+/// time. A snowflake can also be compared directly with a string.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Snowflake(String);
+
+impl Snowflake {
+    /// The snowflake's decimal string form.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The time at which the identified object was created, or `None` if the snowflake isn't a
+    /// valid Discord id.
+    pub fn created_at(&self) -> Option<SystemTime> {
+        let ms = (self.0.parse::<u64>().ok()? >> 22) + DISCORD_EPOCH_MS;
+        Some(UNIX_EPOCH + Duration::from_millis(ms))
+    }
+}
+
+impl Ord for Snowflake {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // For canonical decimal strings, a shorter string is always a smaller number.
+        (self.0.len(), &self.0).cmp(&(other.0.len(), &other.0))
+    }
+}
+
+impl PartialOrd for Snowflake {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for Snowflake {
+    fn from(id: String) -> Self {
+        Snowflake(id)
+    }
+}
+
+impl From<&str> for Snowflake {
+    fn from(id: &str) -> Self {
+        Snowflake(id.to_string())
+    }
+}
+
+impl From<&String> for Snowflake {
+    fn from(id: &String) -> Self {
+        Snowflake(id.clone())
+    }
+}
+
+impl From<u64> for Snowflake {
+    fn from(id: u64) -> Self {
+        Snowflake(id.to_string())
+    }
+}
+
+impl From<Snowflake> for String {
+    fn from(id: Snowflake) -> Self {
+        id.0
+    }
+}
+
+impl Borrow<str> for Snowflake {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Snowflake {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Snowflake {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn created_at() {
+        // The example from Discord's documentation, created at 2016-04-30 11:18:25.796 UTC.
+        let id = Snowflake::from("175928847299117063");
+        assert_eq!(
+            id.created_at(),
+            Some(UNIX_EPOCH + Duration::from_millis(1_462_015_105_796))
+        );
+
+        // The lowest snowflake was created at the Discord epoch itself.
+        assert_eq!(
+            Snowflake::from(0).created_at(),
+            Some(UNIX_EPOCH + Duration::from_millis(DISCORD_EPOCH_MS))
+        );
+
+        assert_eq!(Snowflake::from("not an id").created_at(), None);
+        assert_eq!(Snowflake::from("").created_at(), None);
+    }
+
+    #[test]
+    fn ordering() {
+        let older = Snowflake::from("99999999999999999");
+        let newer = Snowflake::from("175928847299117063");
+        assert!(older < newer);
+        assert!(older.created_at() < newer.created_at());
+        assert_eq!(newer, "175928847299117063");
+    }
+}
@@ -0,0 +1,57 @@
+use std::any::{type_name, Any};
+use std::sync::Arc;
+
+/// Application state shared with handlers, such as database pools or configuration. State is
+/// given to a `Runner` with `Runner::state`, and read from the handler payloads (e.g.
+/// `ApplicationCommand::state`) by its type.
+///
+/// The state also carries the bot token given to the runner with `Runner::bot_token`, for the
+/// payload methods that call Discord's HTTP API.
+#[derive(Clone, Default)]
+pub struct State {
+    value: Option<Arc<dyn Any + Send + Sync>>,
+    #[cfg(feature = "download")]
+    bot_token: Option<Arc<str>>,
+}
+
+impl State {
+    /// Sets the application state, replacing any set before, but keeping the bot token.
+    pub(crate) fn set<S>(&mut self, state: S)
+    where
+        S: Any + Send + Sync,
+    {
+        self.value = Some(Arc::new(state));
+    }
+
+    #[cfg(feature = "download")]
+    pub(crate) fn set_bot_token(&mut self, token: &str) {
+        self.bot_token = Some(token.into());
+    }
+
+    /// The bot token given to the runner, if any.
+    #[cfg(feature = "download")]
+    pub(crate) fn bot_token(&self) -> Option<&str> {
+        self.bot_token.as_deref()
+    }
+
+    /// Gets the state, if the runner was given state of type `S`.
+    pub fn try_get<S: Any>(&self) -> Option<&S> {
+        self.value.as_ref()?.downcast_ref()
+    }
+
+    /// Gets a shared handle to the state, if the runner was given state of type `S`.
+    pub(crate) fn get_arc<S>(&self) -> Option<Arc<S>>
+    where
+        S: Any + Send + Sync,
+    {
+        self.value.clone()?.downcast().ok()
+    }
+
+    /// Gets the state. Panics if the runner wasn't given state of type `S`.
+    pub fn get<S: Any>(&self) -> &S {
+        match self.try_get() {
+            Some(state) => state,
+            None => panic!("Runner has no state of type `{}`!", type_name::<S>()),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+/*!
+ * Serving interactions as a [tower](https://docs.rs/tower) `Service`, so that the interactions
+ * endpoint can be mounted behind any tower-compatible server or middleware stack, e.g. hyper:
+ *
+ * ```ignore
+ * let service = InteractionService::new(&public_key, MyHandler);
+ * ```
+ *
+ * Requires the `tower` feature.
+ */
+
+use super::auth::{HttpResponseParts, Runner};
+use super::handler::InteractionHandler;
+use bytes::Bytes;
+use http::header::CONTENT_TYPE;
+use http::{Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Full};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// A `Service` that verifies and handles interaction requests with a runner. Requests are always
+/// answered, with an error status if they aren't valid interactions, so the service never fails.
+pub struct InteractionService<T> {
+    runner: Arc<Runner<T>>,
+}
+
+impl<T> InteractionService<T>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    /// Creates a service that handles interactions with the given handler, verifying them with the
+    /// given application public key.
+    pub fn new(public_key: &str, handler: T) -> Self {
+        InteractionService::with_runner(Runner::with_handler(public_key, handler))
+    }
+
+    /// Creates a service that handles interactions with a configured runner, e.g. one with
+    /// middleware or application state.
+    pub fn with_runner(runner: Runner<T>) -> Self {
+        InteractionService {
+            runner: Arc::new(runner),
+        }
+    }
+}
+
+impl<T> Clone for InteractionService<T> {
+    fn clone(&self) -> Self {
+        InteractionService {
+            runner: self.runner.clone(),
+        }
+    }
+}
+
+impl<T, B> Service<Request<B>> for InteractionService<T>
+where
+    T: InteractionHandler + Send + Sync + 'static,
+    B: Body + Send + 'static,
+    B::Data: Send,
+{
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let runner = self.runner.clone();
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = body.collect().await.ok().map(|body| body.to_bytes());
+            let res = match body {
+                Some(body) => runner.handle_http(&parts.headers, &body).await,
+                None => HttpResponseParts::error(StatusCode::BAD_REQUEST.as_u16()),
+            };
+
+            Ok(Response::builder()
+                .status(res.status)
+                .header(CONTENT_TYPE, res.content_type)
+                .body(Full::new(Bytes::from(res.body)))
+                .unwrap())
+        })
+    }
+}
@@ -1,176 +1,2765 @@
 use super::discord_types;
-use std::collections::HashMap;
+#[cfg(feature = "download")]
+use super::error::ApiError;
+use super::error::HandlerError;
+use super::from_modal::{FromModal, ModalError};
+use super::message_flags;
+use super::router::Params;
+use super::snowflake::Snowflake;
+use super::state::State;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "download")]
+use std::sync::OnceLock;
 
-/// An top level interaction initiated by the user. Application commands do not require any existing conversation with the bot to be occurring. Currently, only chat application commands (slash commands) are fully supported.
+/// An top level interaction initiated by the user. Application commands do not require any existing conversation with the bot to be occurring. These are either slash commands, or context menu commands used on a user or message (see `target`).
 pub struct ApplicationCommand {
+    /// The id of the registered command that was invoked. Commands with the same name that are
+    /// registered separately (e.g. in different guilds) have different ids.
+    pub command_id: Option<Snowflake>,
     pub command_name: String,
-    pub user_id: String,
+
+    /// Whether the command is a slash command or a context menu command.
+    pub command_type: CommandType,
+
+    pub user_id: Snowflake,
+
+    /// The invoking user's profile.
+    pub user: User,
+
+    /// The invoking user's guild member details, or `None` if the command was invoked in a DM.
+    pub member: Option<Member>,
+
+    /// The guild that the command was invoked in, or `None` if it was invoked in a DM.
+    pub guild_id: Option<Snowflake>,
+
+    /// The channel that the command was invoked in.
+    pub channel_id: Option<Snowflake>,
+
+    /// Details of the channel that the command was invoked in.
+    pub channel: Option<Channel>,
+
+    /// The id of the interaction, used along with `token` to respond to it through Discord's
+    /// webhook endpoints, e.g. to send followup messages.
+    pub interaction_id: Snowflake,
+
+    /// The interaction's continuation token, valid for 15 minutes after the interaction.
+    pub token: String,
+
+    /// The id of the application that the interaction was sent to.
+    pub application_id: Snowflake,
+
+    /// The invoking user's selected language, e.g. `en-US`.
+    pub locale: Option<String>,
+
+    /// The guild's preferred language, if the interaction came from a guild.
+    pub guild_locale: Option<String>,
+
+    /// The application's permissions in the channel the interaction came from, as a bitfield.
+    /// See the `permissions` module for the individual flags.
+    pub app_permissions: u64,
+
+    /// Where the interaction was triggered from, if Discord provided it.
+    pub context: Option<InteractionContext>,
+
+    /// The installations that authorized the interaction, for applications that can be
+    /// installed to users as well as guilds.
+    pub authorizing_owners: AuthorizingOwners,
+
+    /// For monetized applications, the invoking user's (and guild's) active entitlements.
+    pub entitlements: Vec<Entitlement>,
+
+    /// The subcommand that was invoked, if the command has subcommands. For example, invoking
+    /// `/config set channel` gives a subcommand named `channel` in the group `set`.
+    pub subcommand: Option<Subcommand>,
+
+    /// The options (arguments) that the user passed to the command, in the order they were
+    /// provided. If a subcommand was invoked, these are the subcommand's options.
+    pub options: Vec<CommandOption>,
+
+    /// Full objects for the users, roles, channels and attachments referenced by the options.
+    pub resolved: Resolved,
+
+    /// For context menu commands, the id of the user or message that the command was used on.
+    pub target_id: Option<Snowflake>,
+
+    /// For context menu commands, the user or message that the command was used on.
+    pub target: Option<CommandTarget>,
+
+    /// If the invoking member is timed out, the ISO 8601 timestamp at which their timeout ends.
+    /// This is `None` if the member has never been timed out, though note that it may also hold a
+    /// timestamp in the past for a timeout that has already expired.
+    pub timed_out_until: Option<String>,
+
+    /// The interaction's `data`, as Discord sent it, for reading fields that aren't modeled here.
+    pub raw_data: serde_json::Value,
+
+    /// The application state given to the runner with `Runner::state`.
+    pub state: State,
+
+    /// The invoking user, once fetched by `fetch_user`.
+    #[cfg(feature = "download")]
+    fetched_user: OnceLock<User>,
+}
+
+/// An interaction sent while the user is typing an application command option that has
+/// autocomplete enabled, asking for choices to suggest.
+pub struct AutocompleteRequest {
+    /// The command being typed, with the options filled in so far. Options whose partial values
+    /// don't parse as their registered type are left out.
+    pub command: ApplicationCommand,
+    /// The name of the option that the user is typing. This is only `None` if Discord marked no
+    /// option as focused, which it shouldn't.
+    pub focused: Option<String>,
+    /// What the user has typed into the focused option so far.
+    pub value: String,
+}
+
+/// The kinds of application command.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CommandType {
+    /// A slash command, typed into the chat box.
+    ChatInput,
+    /// A command in the context menu of a user.
+    User,
+    /// A command in the context menu of a message.
+    Message,
+    /// The command that launches an application's activity.
+    PrimaryEntryPoint,
+}
+
+/// The user or message that a context menu command was used on.
+pub enum CommandTarget {
+    User {
+        user: User,
+        /// The user's guild member details, or `None` if the command was used in a DM.
+        member: Option<Member>,
+    },
+    Message(SourceMessage),
+}
+
+/// A slash command, as viewed with `ApplicationCommand::as_slash_command`.
+pub struct SlashCommandView<'a> {
+    pub name: &'a str,
+    /// The subcommand that was invoked, if the command has subcommands.
+    pub subcommand: Option<&'a Subcommand>,
+    /// The options that the user passed to the command (or subcommand).
+    pub options: &'a [CommandOption],
+}
+
+/// A user context menu command, as viewed with `ApplicationCommand::as_user_command`.
+pub struct UserCommandView<'a> {
+    pub name: &'a str,
+    /// The user that the command was used on.
+    pub user: &'a User,
+    /// The user's guild member details, or `None` if the command was used in a DM.
+    pub member: Option<&'a Member>,
+}
+
+/// A message context menu command, as viewed with `ApplicationCommand::as_message_command`.
+pub struct MessageCommandView<'a> {
+    pub name: &'a str,
+    /// The message that the command was used on.
+    pub message: &'a SourceMessage,
+}
+
+/// A subcommand of an application command, optionally nested in a subcommand group.
+pub struct Subcommand {
+    pub group: Option<String>,
+    pub name: String,
+}
+
+/// An option (argument) passed to an application command by the user.
+pub struct CommandOption {
+    pub name: String,
+    pub value: CommandOptionValue,
+}
+
+/// The value of a command option, according to the option's registered type. Users, channels,
+/// roles, mentionables and attachments are given by their ids.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CommandOptionValue {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    User(Snowflake),
+    Channel(Snowflake),
+    Role(Snowflake),
+    Mentionable(Snowflake),
+    Number(f64),
+    Attachment(Snowflake),
+}
+
+/// Where an interaction was triggered from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InteractionContext {
+    /// A guild channel.
+    Guild,
+    /// A DM with the application's bot user.
+    BotDm,
+    /// A DM or group DM other than one with the bot user, for applications installed to users.
+    PrivateChannel,
+}
+
+/// The installations of an application that authorized an interaction. An application installed
+/// to both a guild and a user may be authorized by either installation, or both.
+pub struct AuthorizingOwners {
+    /// If the application's guild installation authorized the interaction, the id of the guild.
+    /// This is `"0"` if the interaction came from a DM with the bot user.
+    pub guild_install: Option<Snowflake>,
+    /// If a user installation authorized the interaction, the id of the user.
+    pub user_install: Option<Snowflake>,
+}
+
+/// An entitlement to a premium offering (SKU) of a monetized application, held by a user or
+/// guild. Timestamps are in ISO 8601 format.
+pub struct Entitlement {
+    pub id: Snowflake,
+    pub sku_id: Snowflake,
+    /// The user that holds the entitlement, if it's held by a user.
+    pub user_id: Option<Snowflake>,
+    /// The guild that holds the entitlement, if it's held by a guild.
+    pub guild_id: Option<Snowflake>,
+    pub entitlement_type: EntitlementType,
+    /// When the entitlement starts to be valid, or `None` for test entitlements.
+    pub starts_at: Option<String>,
+    /// When the entitlement expires, or `None` for test entitlements and entitlements that
+    /// don't expire.
+    pub ends_at: Option<String>,
+    /// For consumable SKUs, whether the entitlement has been consumed.
+    pub consumed: bool,
+}
+
+/// How an entitlement was acquired.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntitlementType {
+    Purchase,
+    PremiumSubscription,
+    DeveloperGift,
+    TestModePurchase,
+    FreePurchase,
+    UserGift,
+    PremiumPurchase,
+    ApplicationSubscription,
+    /// An entitlement type that this library doesn't know about yet.
+    Other(u8),
+}
+
+/// The full objects behind ids referenced by an interaction, e.g. the user given in a user option.
+/// Each map is keyed by id, and only contains the objects that the interaction referenced.
+pub struct Resolved {
+    pub users: HashMap<Snowflake, User>,
+    /// Guild member details for users referenced in a guild. Empty for interactions in DMs.
+    pub members: HashMap<Snowflake, Member>,
+    pub roles: HashMap<Snowflake, Role>,
+    pub channels: HashMap<Snowflake, Channel>,
+    pub messages: HashMap<Snowflake, SourceMessage>,
+    pub attachments: HashMap<Snowflake, Attachment>,
+}
+
+/// A Discord user.
+pub struct User {
+    pub id: Snowflake,
+    pub username: String,
+    /// The user's display name, if they've set one. Use `display_name` to fall back to the
+    /// username.
+    pub global_name: Option<String>,
+    /// The user's legacy discriminator (the digits after the `#`), or `"0"` for users that have
+    /// migrated to unique usernames.
+    pub discriminator: String,
+    /// The user's avatar hash, if they've set an avatar. Use `avatar_url` to get a displayable
+    /// image.
+    pub avatar: Option<String>,
+    /// The user's profile banner hash, if they've set a banner. Discord leaves this out of the
+    /// users in interactions; see `ApplicationCommand::fetch_user`.
+    pub banner: Option<String>,
+    /// The user's profile color, as an RGB integer. Like `banner`, Discord leaves this out of the
+    /// users in interactions.
+    pub accent_color: Option<u32>,
+}
+
+/// Guild-specific details of a user. Timestamps are in ISO 8601 format.
+pub struct Member {
+    /// The member's nickname in the guild, if they've set one.
+    pub nick: Option<String>,
+    /// The member's guild-specific avatar hash, if they've set one.
+    pub avatar: Option<String>,
+    /// The ids of the member's roles.
+    pub roles: Vec<Snowflake>,
+    pub joined_at: Option<String>,
+    /// When the member started boosting the guild, if they are boosting it.
+    pub premium_since: Option<String>,
+    /// The member's permissions in the channel the interaction came from, as a bitfield.
+    pub permissions: Option<u64>,
+}
+
+/// A guild role.
+pub struct Role {
+    pub id: Snowflake,
+    pub name: String,
+    /// The role's color, as an RGB integer. Zero means the role has no color.
+    pub color: u32,
+    /// If true, members with this role are displayed separately in the member list.
+    pub hoist: bool,
+    /// The role's icon hash, if it has an icon.
+    pub icon: Option<String>,
+    /// The standard emoji shown as the role's icon, if it has one instead of an icon.
+    pub unicode_emoji: Option<String>,
+    pub position: i32,
+    /// The role's permissions, as a bitfield.
+    pub permissions: u64,
+    /// If true, this role is managed by an integration, such as a bot.
+    pub managed: bool,
+    pub mentionable: bool,
+    /// The bot that the role belongs to, if it's a bot's managed role.
+    pub bot_id: Option<Snowflake>,
+    /// The integration that manages the role, if any.
+    pub integration_id: Option<Snowflake>,
+}
+
+/// A partial channel object.
+pub struct Channel {
+    pub id: Snowflake,
+    pub channel_type: ChannelType,
+    pub name: Option<String>,
+    /// The category containing the channel, or the parent channel of a thread.
+    pub parent_id: Option<Snowflake>,
+    /// The invoking user's permissions in the channel, as a bitfield.
+    pub permissions: Option<u64>,
+}
+
+/// The type of a channel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChannelType {
+    GuildText,
+    Dm,
+    GuildVoice,
+    GroupDm,
+    GuildCategory,
+    GuildAnnouncement,
+    AnnouncementThread,
+    PublicThread,
+    PrivateThread,
+    GuildStageVoice,
+    GuildDirectory,
+    GuildForum,
+    GuildMedia,
+    /// A channel type that this library doesn't know about yet.
+    Other(u8),
+}
+
+/// A file uploaded by the user, e.g. through an attachment option.
+pub struct Attachment {
+    pub id: Snowflake,
+    pub filename: String,
+    /// The attachment's alt text, if any.
+    pub description: Option<String>,
+    /// The attachment's media type, e.g. `image/png`.
+    pub content_type: Option<String>,
+    /// The attachment's size, in bytes. `None` if Discord left it out.
+    pub size: Option<u64>,
+    /// Where the attachment can be downloaded from. `None` if Discord left it out.
+    pub url: Option<String>,
+    /// A proxied URL for the attachment, e.g. for fetching images through Discord's media proxy.
+    pub proxy_url: Option<String>,
+    /// The image's height, if the attachment is an image.
+    pub height: Option<u32>,
+    /// The image's width, if the attachment is an image.
+    pub width: Option<u32>,
 }
 
-/// An interaction caused by the user's interaction with a message component embedded in a chat message. Currently, only button presses are supported.
+/// An interaction caused by the user's interaction with a message component embedded in a chat message, i.e. a button press or a select menu choice.
 pub struct MessageComponent {
     pub id: String,
+    pub user_id: Snowflake,
+
+    /// The values of the options that the user chose, if the component is a select menu. Empty
+    /// for buttons. For user, role, mentionable and channel select menus, these are the ids of
+    /// the chosen objects; see also `selected_ids`.
+    pub values: Vec<String>,
+
+    /// Full objects for the users, members, roles and channels chosen in a user, role,
+    /// mentionable or channel select menu.
+    pub resolved: Resolved,
+
+    /// The invoking user's profile.
+    pub user: User,
+
+    /// The invoking user's guild member details, or `None` if the component was pressed in a DM.
+    pub member: Option<Member>,
+
+    /// The guild that the component was interacted with in, or `None` if the component was
+    /// pressed in a DM.
+    pub guild_id: Option<Snowflake>,
+
+    /// The channel that the component was interacted with in.
+    pub channel_id: Option<Snowflake>,
+
+    /// Details of the channel that the component was interacted with in.
+    pub channel: Option<Channel>,
+
+    /// The id of the interaction, used along with `token` to respond to it through Discord's
+    /// webhook endpoints, e.g. to send followup messages.
+    pub interaction_id: Snowflake,
+
+    /// The interaction's continuation token, valid for 15 minutes after the interaction.
+    pub token: String,
+
+    /// The id of the application that the interaction was sent to.
+    pub application_id: Snowflake,
+
+    /// The invoking user's selected language, e.g. `en-US`.
+    pub locale: Option<String>,
+
+    /// The guild's preferred language, if the interaction came from a guild.
+    pub guild_locale: Option<String>,
+
+    /// The application's permissions in the channel the interaction came from, as a bitfield.
+    /// See the `permissions` module for the individual flags.
+    pub app_permissions: u64,
+
+    /// Where the interaction was triggered from, if Discord provided it.
+    pub context: Option<InteractionContext>,
+
+    /// The installations that authorized the interaction, for applications that can be
+    /// installed to users as well as guilds.
+    pub authorizing_owners: AuthorizingOwners,
+
+    /// For monetized applications, the invoking user's (and guild's) active entitlements.
+    pub entitlements: Vec<Entitlement>,
 
     /// The message that this component was originally attached to.
     pub source: SourceMessage,
+
+    /// The interaction's `data`, as Discord sent it, for reading fields that aren't modeled here.
+    pub raw_data: serde_json::Value,
+
+    /// The application state given to the runner with `Runner::state`.
+    pub state: State,
+
+    /// The parts of the id captured by the `Router` pattern that it matched, if any.
+    pub params: Params,
 }
 
 /// An interaction type caused by the user submitting a completed modal form. Modals are the
 /// primary way of retrieving text input from the user.
 pub struct ModalSubmit {
     pub id: String,
+    pub user_id: Snowflake,
+
+    /// The submitting user's profile.
+    pub user: User,
+
+    /// The submitting user's guild member details, or `None` if the modal was submitted in a DM.
+    pub member: Option<Member>,
+    pub values: HashMap<String, String>,
+
+    /// The values of the options that the user chose in the modal's select menus, keyed by the
+    /// select menus' ids.
+    pub selections: HashMap<String, Vec<String>>,
+
+    /// Every input in the modal, text fields and select menus alike, in the order they appeared.
+    pub fields: Vec<ModalInput>,
+
+    /// The guild that the modal was submitted in, or `None` if it was submitted in a DM.
+    pub guild_id: Option<Snowflake>,
+
+    /// The channel that the modal was submitted in.
+    pub channel_id: Option<Snowflake>,
+
+    /// Details of the channel that the modal was submitted in.
+    pub channel: Option<Channel>,
+
+    /// The id of the interaction, used along with `token` to respond to it through Discord's
+    /// webhook endpoints, e.g. to send followup messages.
+    pub interaction_id: Snowflake,
+
+    /// The interaction's continuation token, valid for 15 minutes after the interaction.
+    pub token: String,
+
+    /// The id of the application that the interaction was sent to.
+    pub application_id: Snowflake,
+
+    /// The invoking user's selected language, e.g. `en-US`.
+    pub locale: Option<String>,
+
+    /// The guild's preferred language, if the interaction came from a guild.
+    pub guild_locale: Option<String>,
+
+    /// The application's permissions in the channel the interaction came from, as a bitfield.
+    /// See the `permissions` module for the individual flags.
+    pub app_permissions: u64,
+
+    /// Where the interaction was triggered from, if Discord provided it.
+    pub context: Option<InteractionContext>,
+
+    /// The installations that authorized the interaction, for applications that can be
+    /// installed to users as well as guilds.
+    pub authorizing_owners: AuthorizingOwners,
+
+    /// For monetized applications, the invoking user's (and guild's) active entitlements.
+    pub entitlements: Vec<Entitlement>,
+
+    /// The message whose component opened this modal, or `None` if the modal was opened by an
+    /// application command.
+    pub source: Option<SourceMessage>,
+
+    /// The interaction's `data`, as Discord sent it, for reading fields that aren't modeled here.
+    pub raw_data: serde_json::Value,
+
+    /// The application state given to the runner with `Runner::state`.
+    pub state: State,
+
+    /// The parts of the id captured by the `Router` pattern that it matched, if any.
+    pub params: Params,
+}
+
+/// One of the inputs of a submitted modal.
+pub struct ModalInput {
+    pub id: String,
+    pub value: ModalValue,
+}
+
+/// What the user entered in an input of a submitted modal.
+pub enum ModalValue {
+    /// The text entered in a text field.
+    Text(String),
+    /// The values of the options chosen in a select menu.
+    Selection(Vec<String>),
+}
+
+/// The text that a user entered in a modal that failed validation, recovered from the "Try again"
+/// button of the message sent by `ModalSubmit::retry`.
+pub struct ModalRetry {
+    /// The id of the modal to reopen.
+    pub modal_id: String,
+    /// The non-empty values of the modal's text fields that fit in the button's custom id, keyed
+    /// by the fields' ids. Pass these to `Modal::prefill` to reopen the modal as the user left it.
     pub values: HashMap<String, String>,
-    /// The message that this modal was originally attached to.
-    pub source: SourceMessage,
 }
 
+/// The prefix of the custom id of the "Try again" button sent by `ModalSubmit::retry`.
+const MODAL_RETRY_PREFIX: &str = "modal_retry:";
+
+/// The most characters Discord allows in a component's custom id.
+const MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+/// The colors of the embeds built by `Embed::error` and `Embed::success`, matching Discord's own
+/// red and green.
+const ERROR_COLOR: u32 = 0xED4245;
+const SUCCESS_COLOR: u32 = 0x57F287;
+
 /// A message that a message component or modal was originally attached to. This allows the
 /// application to maintain some notion of "state", by reasoning based on the source message's
-/// text.
+/// contents.
 pub struct SourceMessage {
+    /// The message's id. Applications can use this to correlate interactions with records they
+    /// stored when the message was created.
+    pub id: Snowflake,
+    pub text: String,
+    /// The user (usually this application) that sent the message.
+    pub author: Option<User>,
+    /// When the message was sent, in ISO 8601 format.
+    pub timestamp: Option<String>,
+    /// When the message was last edited, in ISO 8601 format, if it has been edited.
+    pub edited_timestamp: Option<String>,
+    /// The message's flags, as a bitfield. See the `message_flags` module for the individual flags.
+    pub flags: u64,
+    pub embeds: Vec<Embed>,
+    /// The buttons currently attached to the message, in order, including those nested in the
+    /// sections and containers of a layout message.
+    pub buttons: Vec<Button>,
+    pub attachments: Vec<Attachment>,
+    /// The message's poll, if it has one, including the current vote counts.
+    pub poll: Option<Poll>,
+}
+
+/// A rich embed, displayed below a message's text.
+pub struct Embed {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// The URL that the title links to.
+    pub url: Option<String>,
+    /// The timestamp displayed in the footer, in ISO 8601 format.
+    pub timestamp: Option<String>,
+    /// The color of the embed's left border, as an RGB integer.
+    pub color: Option<u32>,
+    pub footer: Option<EmbedFooter>,
+    /// The URL of the embed's image.
+    pub image: Option<String>,
+    /// The URL of the embed's thumbnail.
+    pub thumbnail: Option<String>,
+    pub author: Option<EmbedAuthor>,
+    pub fields: Vec<EmbedField>,
+}
+
+/// The footer of an embed.
+pub struct EmbedFooter {
     pub text: String,
+    pub icon_url: Option<String>,
+}
+
+/// The author of an embed, displayed above its title.
+pub struct EmbedAuthor {
+    pub name: String,
+    pub url: Option<String>,
+    pub icon_url: Option<String>,
+}
+
+/// A field in an embed.
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    /// If true, the field may be displayed side by side with other inline fields.
+    pub inline: bool,
 }
 
 /// A response to an interaction. This response can either be a message in chat, or a modal, which
 /// will pop up over the user's screen.
+// Only one response is built per interaction, so boxing the message isn't worth the noise.
+#[allow(clippy::large_enum_variant)]
 pub enum Response {
     Message(Message),
     Modal(Modal),
+    /// Acknowledges the interaction without a message yet, showing a "thinking..." state to the
+    /// user. Handlers that need more than Discord's 3 seconds to respond can defer, and then send
+    /// the real message within 15 minutes by editing the original response through Discord's
+    /// webhook endpoints, using the interaction's `token`. If `ephemeral` is true, the eventual
+    /// message will be visible to only the user.
+    Deferred {
+        ephemeral: bool,
+    },
+    /// Acknowledges a message component interaction without changing anything visibly. The
+    /// message can still be edited later through Discord's webhook endpoints, using the
+    /// interaction's `token`. Not valid for application commands.
+    DeferredUpdate,
+    /// Updates the message that the component is attached to, as an edit (see `Message::edit`),
+    /// and then sends a followup, e.g. a private "You claimed it!" to the user while the public
+    /// message changes to show that it's claimed. See `Response::update_with_ephemeral_followup`.
+    /// Not valid for application commands.
+    ///
+    /// This takes two calls to Discord's API, which the `Runner` makes in order: it first sends the
+    /// update as the interaction's response, through Discord's callback endpoint, and only once
+    /// Discord has accepted it creates the followup, since followups can't be sent before the
+    /// interaction has been responded to. Discord's request is then answered with a 202 Accepted
+    /// status and no body. The update must still be sent within Discord's 3 seconds, and the
+    /// followup isn't sent if the update fails. This response needs a `Runner`, which can make the
+    /// calls; `handle_interaction` answers it with the fallback message instead. Requires the
+    /// `download` feature.
+    #[cfg(feature = "download")]
+    UpdateWithFollowup {
+        update: Message,
+        followup: Followup,
+    },
+    /// Shows Discord's native upsell, prompting the user to buy the application's premium SKU,
+    /// e.g. when the user lacks the entitlement a command requires. Discord has deprecated this
+    /// response in favor of messages with premium buttons (see `Message::premium_button`), but
+    /// still supports it.
+    PremiumRequired,
+    /// Launches the application's Activity for the user. Only available to applications with
+    /// Activities enabled, and not valid for modal submits.
+    LaunchActivity,
+    /// Suggests choices for the option that the user is typing, in answer to an autocomplete
+    /// interaction. Discord allows up to 25 choices.
+    Autocomplete(Vec<Choice>),
+    /// Sends the given JSON as the response, as it is, for Discord features that the other
+    /// responses don't cover yet. Its `type` must be one of Discord's interaction callback types.
+    /// Raw responses aren't checked, and the `Runner`'s mention suppression doesn't apply to them.
+    Raw(serde_json::Value),
+}
+
+/// A suggested value for a command option, offered to the user as they type.
+pub struct Choice {
+    /// The text shown to the user.
+    pub name: String,
+    /// Translations of `name`, keyed by locale (e.g. `fr`), shown to users with those locales.
+    pub name_localizations: HashMap<String, String>,
+    /// The value that the option will be given if the user picks the choice.
+    pub value: ChoiceValue,
+}
+
+/// The value of a choice, which must match the type of the option it's suggested for.
+pub enum ChoiceValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+}
+
+/// A followup message, sent to the interaction's channel after the initial response, within 15
+/// minutes of the interaction. A followup is a message of its own, so it chooses its own
+/// visibility: with `ephemeral`, it's visible to only the user, whether the initial response was
+/// public or not, e.g. for a private receipt after a public reply.
+///
+/// This differs from completing a deferred response (see `Response::Deferred`). The first
+/// followup after deferring replaces the "thinking..." state instead, and its visibility must
+/// match the deferral's: it's ephemeral if and only if the deferral was, whatever its own flags.
+pub struct Followup {
+    /// The message to send. Followups create new messages, so the message can't be an edit.
+    pub message: Message,
 }
 
 /// A message response, resulting in a message in chat.
 pub struct Message {
     pub text: String,
-    pub buttons: Vec<Button>,
-    /// If true, the message will be visible to only the recipient.
-    pub ephemeral: bool,
+    /// The embeds displayed below the message's text. Discord allows up to 10.
+    pub embeds: Vec<Embed>,
+    /// The message's components, in the order they're displayed: rows of buttons and select
+    /// menus, and for layout messages, layout components. See `TopLevelComponent`.
+    pub components: Vec<TopLevelComponent>,
+    /// Files uploaded with the message. Discord allows up to 10.
+    pub files: Vec<FileUpload>,
+    /// If true, the message's text will be read aloud by text-to-speech to users viewing the
+    /// channel.
+    pub tts: bool,
+    /// A poll attached to the message. Polls can only be attached to new messages, not edits.
+    pub poll: Option<Poll>,
+    /// The message's flags, as a bitfield. See the `message_flags` module for the individual flags,
+    /// e.g. `EPHEMERAL`, under which the message will be visible to only the recipient.
+    pub flags: u64,
     /// If true, the message will replace the original message.
     pub edit: bool,
+    /// The parts of the original message that an edit keeps as they are. Parts that aren't kept
+    /// are replaced, and so cleared if the edit doesn't set them.
+    pub keep: Keep,
+    /// Which mentions in the message's text will notify the mentioned users and roles. If this is
+    /// `None`, the `Runner` decides; by default, no one is notified.
+    pub allowed_mentions: Option<AllowedMentions>,
+}
+
+/// A file to upload with a message, e.g. a generated image. Images are displayed inline, and other
+/// files as downloads.
+pub struct FileUpload {
+    pub filename: String,
+    pub data: Vec<u8>,
+    /// Alt text for the file, describing an image for screen readers.
+    pub description: Option<String>,
+}
+
+/// A poll, which users vote on by choosing one (or more) of its answers. Polls are created by
+/// attaching them to a message response, and can be read back, with their vote counts, from the
+/// source message of a later interaction.
+pub struct Poll {
+    pub question: String,
+    /// The answers to choose from. Discord allows between 1 and 10.
+    pub answers: Vec<PollAnswer>,
+    /// How long the poll accepts votes, in hours, up to 32 days. Only used when creating a poll.
+    pub duration: u32,
+    /// If true, users can choose more than one answer.
+    pub allow_multiselect: bool,
+    /// When the poll ends, in ISO 8601 format, for polls read from a message.
+    pub expiry: Option<String>,
+    /// The vote counts, for polls read from a message. `None` if Discord didn't include them.
+    pub results: Option<PollResults>,
+}
+
+/// An answer in a poll.
+pub struct PollAnswer {
+    /// The answer's id, assigned by Discord and used to look up its vote count in the results.
+    /// Ignored when creating a poll.
+    pub id: u32,
+    pub text: String,
+    /// The emoji displayed before the answer's text.
+    pub emoji: Option<Emoji>,
+}
+
+/// The vote counts of a poll.
+pub struct PollResults {
+    /// If true, the poll has ended, and the counts are final.
+    pub finalized: bool,
+    /// The number of votes for each answer, keyed by answer id. Answers without votes may be
+    /// absent.
+    pub counts: HashMap<u32, u32>,
+    /// The ids of the answers that the invoking user voted for.
+    pub voted: Vec<u32>,
+}
+
+/// The parts of an edited message to keep as they are. See `Message::keep_text`,
+/// `Message::keep_embeds` and `Message::keep_components`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Keep {
+    pub text: bool,
+    pub embeds: bool,
+    /// Buttons, select menus and layout components.
+    pub components: bool,
+}
+
+/// The kinds of mentions in a message's text that will notify ("ping") the mentioned users and
+/// roles. Mentions that aren't allowed are still rendered, but don't notify anyone.
+pub struct AllowedMentions {
+    /// If true, user mentions will notify the mentioned users.
+    pub users: bool,
+    /// If true, role mentions will notify the mentioned roles.
+    pub roles: bool,
+    /// If true, `@everyone` and `@here` will notify the channel.
+    pub everyone: bool,
+    /// Specific users whose mentions will notify them, when `users` is false.
+    pub user_ids: Vec<Snowflake>,
+    /// Specific roles whose mentions will notify them, when `roles` is false.
+    pub role_ids: Vec<Snowflake>,
+    /// If true, and the message is a reply, the author of the message being replied to will be
+    /// notified.
+    pub replied_user: bool,
 }
 
 /// A button component, which the user can interact with. If a user clicks such
 /// a button, it will spawn a message component interaction.
 pub struct Button {
+    /// The id sent to the application when the button is clicked. Empty for link and premium
+    /// buttons, which don't spawn interactions.
     pub id: String,
     pub text: String,
+    pub style: ButtonStyle,
+    /// The URL that a link button opens.
+    pub url: Option<String>,
+    /// The SKU that a premium button offers.
+    pub sku_id: Option<Snowflake>,
+    /// The emoji displayed before the button's text.
+    pub emoji: Option<Emoji>,
+    /// If true, the button is greyed out, and can't be clicked.
+    pub disabled: bool,
+}
+
+/// The colors a button can be displayed in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ButtonStyle {
+    /// Blurple, for the main action.
+    Primary,
+    /// Grey, for secondary actions.
+    Secondary,
+    /// Green, e.g. for confirming.
+    Success,
+    /// Red, for destructive actions.
+    Danger,
+    /// Grey, opening a URL instead of spawning an interaction.
+    Link,
+    /// Blurple, offering an SKU for purchase instead of spawning an interaction. Discord displays
+    /// the SKU's name and price in place of the button's text.
+    Premium,
+}
+
+/// A component that can be placed in a row with `Message::row`. A row holds either up to 5 buttons,
+/// or a single select menu.
+pub enum RowComponent {
+    Button(Button),
+    Select(SelectMenu),
+}
+
+/// A component at the top level of a message, as sent to Discord. A message without a layout holds
+/// only action rows, at most 5 of them. A layout message, i.e. one with layout components or the
+/// `IS_COMPONENTS_V2` flag, is built from components (Discord's "Components V2") rather than text
+/// and embeds, and can mix layout components and rows.
+pub enum TopLevelComponent {
+    /// A row holding either up to 5 buttons, or a single select menu.
+    ActionRow(Vec<RowComponent>),
+    /// A component of a layout message, e.g. a container.
+    Layout(LayoutComponent),
+}
+
+/// Rows of components declared one by one, as an alternative to `Message::row`, e.g.
+/// `Layout::new().row(|r| r.button(yes).button(no)).row(|r| r.select(menu))`. Add the rows to a
+/// message with `Message::rows`, which checks them along with the rest of the message.
+#[derive(Default)]
+pub struct Layout {
+    pub rows: Vec<Vec<RowComponent>>,
+}
+
+/// A row of components being built by `Layout::row`.
+#[derive(Default)]
+pub struct Row {
+    pub components: Vec<RowComponent>,
+}
+
+/// A component in a message's layout (see `Message::component`). Layout components can hold
+/// markdown text, media and interactive components, and can be grouped into containers. A
+/// message's layout can hold at most 40 components in total, counting nested components.
+pub enum LayoutComponent {
+    /// Markdown text.
+    Text(String),
+    Section(Section),
+    /// A gallery of up to 10 images or videos.
+    MediaGallery(Vec<MediaItem>),
+    /// Vertical space between components. If `divider` is true, a line is drawn through it, and
+    /// if `large` is true, there's more space.
+    Separator {
+        divider: bool,
+        large: bool,
+    },
+    /// A file uploaded with the message (see `Message::file`), displayed as a download.
+    File {
+        filename: String,
+        spoiler: bool,
+    },
+    Container(Container),
+    /// A row holding either up to 5 buttons, or a single select menu.
+    Row(Vec<RowComponent>),
+}
+
+/// Between 1 and 3 pieces of markdown text, displayed alongside an accessory.
+pub struct Section {
+    pub texts: Vec<String>,
+    pub accessory: SectionAccessory,
+}
+
+/// The component displayed alongside a section's text.
+pub enum SectionAccessory {
+    Thumbnail(MediaItem),
+    Button(Button),
+}
+
+/// An image or video, for thumbnails and media galleries.
+pub struct MediaItem {
+    /// The URL of the media, or `attachment://<filename>` for a file uploaded with the message.
+    pub url: String,
+    /// Alt text for the media.
+    pub description: Option<String>,
+    /// If true, the media is blurred until the user clicks it.
+    pub spoiler: bool,
+}
+
+/// A group of layout components, displayed in a box like an embed.
+pub struct Container {
+    pub components: Vec<LayoutComponent>,
+    /// The color of the container's left border, as an RGB integer.
+    pub accent_color: Option<u32>,
+    /// If true, the container's contents are blurred until the user clicks them.
+    pub spoiler: bool,
+}
+
+/// A dropdown menu of options, from which the user can choose one or more. If a user makes a
+/// choice, it will spawn a message component interaction with the chosen options' values.
+pub struct SelectMenu {
+    pub id: String,
+    /// What the user chooses from.
+    pub menu_type: SelectMenuType,
+    /// The text shown when no option is chosen.
+    pub placeholder: Option<String>,
+    /// The options to choose from, for string select menus. Discord allows between 1 and 25, with
+    /// unique values. Other select menus are populated by Discord, and must not have options.
+    pub options: Vec<SelectOption>,
+    /// The minimum number of options that must be chosen.
+    pub min_values: u8,
+    /// The maximum number of options that can be chosen.
+    pub max_values: u8,
+    /// If true, the select menu is greyed out, and can't be used.
+    pub disabled: bool,
+}
+
+/// The kinds of select menus. String select menus offer the options given by the application, and
+/// the others are populated by Discord, with e.g. the members of the guild.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelectMenuType {
+    String,
+    User,
+    Role,
+    /// Offers both users and roles.
+    Mentionable,
+    Channel,
+}
+
+/// An option in a select menu.
+pub struct SelectOption {
+    /// The text shown to the user.
+    pub label: String,
+    /// The value sent to the application when the option is chosen.
+    pub value: String,
+    /// Additional text shown below the label.
+    pub description: Option<String>,
+    /// If true, the option is chosen by default.
+    pub default: bool,
+    /// The emoji displayed before the option's label.
+    pub emoji: Option<Emoji>,
+}
+
+/// An emoji displayed on a button or select option.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Emoji {
+    /// A standard emoji, given as the emoji itself, e.g. `"👍"`.
+    Unicode(String),
+    /// A custom emoji uploaded to a guild, or to the application.
+    Custom {
+        id: Snowflake,
+        name: String,
+        animated: bool,
+    },
 }
 
 /// A modal response, which allows the user to input text information. A modal cannot be a response
 /// to a modal submit interaction.
-pub struct Modal {
-    pub id: String,
-    pub title: String,
-    pub fields: Vec<TextField>,
+///
+/// Discord requires every modal to have an id and a title, so these are tracked in the modal's
+/// type: `Modal::new()` creates a `Modal<(), ()>`, and `id` and `title` fill in the corresponding
+/// type parameters. Only a `Modal<String, String>` (written just `Modal`) can be used in a
+/// `Response`, so forgetting either one is a compile-time error rather than a rejected response.
+pub struct Modal<I = String, T = String> {
+    pub id: I,
+    pub title: T,
+    pub fields: Vec<ModalComponent>,
+}
+
+/// A component included in a modal: either a text field, or a select menu with a label.
+pub enum ModalComponent {
+    Text(ModalField),
+    Select { label: String, select: SelectMenu },
 }
 
-/// A text field included in a modal.
-pub struct TextField {
+/// A text field included in a modal. Fields can be added with `Modal::field` and configured with
+/// the modal's convenience methods, or built with `ModalField`'s own convenience methods and added
+/// with `Modal::add_field`.
+pub struct ModalField {
     pub id: String,
     pub label: String,
+    pub style: TextInputStyle,
+    /// The text shown when the field is empty.
+    pub placeholder: Option<String>,
+    /// The minimum number of characters the user must enter, from 0 to 4000.
+    pub min_length: Option<u16>,
+    /// The maximum number of characters the user can enter, from 1 to 4000.
+    pub max_length: Option<u16>,
+    /// If false, the modal can be submitted with the field left empty.
+    pub required: bool,
+    /// The text that the field is prefilled with.
+    pub value: Option<String>,
 }
 
-/// Convenience methods for building messages.
-impl Message {
-    /// Creates a new message, defaulting to non-ephemeral, and non-editing.
-    pub fn new() -> Self {
-        Message {
-            text: "".to_string(),
-            buttons: Vec::new(),
-            ephemeral: false,
-            edit: false,
+/// The sizes a modal's text field can be displayed in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextInputStyle {
+    /// A single line of text.
+    Short,
+    /// A larger box, for text that spans multiple lines.
+    Paragraph,
+}
+
+/// Convenience methods for reading command options. Each of the typed accessors returns `None`
+/// if the option wasn't provided, or if it has a different type.
+impl ApplicationCommand {
+    /// Gets the value of the option with the given name.
+    pub fn option(&self, name: &str) -> Option<&CommandOptionValue> {
+        self.options
+            .iter()
+            .find(|opt| opt.name == name)
+            .map(|opt| &opt.value)
+    }
+
+    /// Gets the value of a string option.
+    pub fn option_str(&self, name: &str) -> Option<&str> {
+        match self.option(name)? {
+            CommandOptionValue::String(s) => Some(s),
+            _ => None,
         }
     }
 
-    /// Sets the `text` field on the message.
-    pub fn text(mut self, text: &str) -> Self {
-        self.text = text.to_string();
-        self
+    /// Gets the value of an integer option.
+    pub fn option_int(&self, name: &str) -> Option<i64> {
+        match self.option(name)? {
+            CommandOptionValue::Integer(n) => Some(*n),
+            _ => None,
+        }
     }
 
-    /// Adds a button to the message.
-    pub fn button(mut self, id: &str, text: &str) -> Self {
-        self.buttons.push(Button {
-            id: id.to_string(),
-            text: text.to_string(),
-        });
-        self
+    /// Gets the value of a number option.
+    pub fn option_number(&self, name: &str) -> Option<f64> {
+        match self.option(name)? {
+            CommandOptionValue::Number(n) => Some(*n),
+            _ => None,
+        }
     }
 
-    /// Sets the message to be ephemeral.
-    pub fn ephemeral(mut self) -> Self {
-        self.ephemeral = true;
-        self
+    /// Gets the value of a boolean option.
+    pub fn option_bool(&self, name: &str) -> Option<bool> {
+        match self.option(name)? {
+            CommandOptionValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
     }
 
-    /// Sets the message to edit the discord message that spawned it.
-    pub fn edit(mut self) -> Self {
-        self.edit = true;
-        self
+    /// Gets the id of the user given in a user option.
+    pub fn option_user(&self, name: &str) -> Option<&Snowflake> {
+        match self.option(name)? {
+            CommandOptionValue::User(id) => Some(id),
+            _ => None,
+        }
     }
-}
 
-/// Convenience methods for building modals.
-impl Modal {
-    /// Creates a new modal.
-    pub fn new() -> Self {
-        Modal {
-            id: "unknown modal".to_string(),
-            title: "".to_string(),
-            fields: Vec::new(),
+    /// Gets the full user object for the user given in a user option.
+    pub fn option_resolved_user(&self, name: &str) -> Option<&User> {
+        self.resolved.users.get(self.option_user(name)?)
+    }
+
+    /// Gets the guild member for the user given in a user option, with e.g. their roles in the
+    /// guild. There is none in DMs, where no members are resolved.
+    pub fn get_resolved_member(&self, name: &str) -> Option<&Member> {
+        self.resolved.members.get(self.option_user(name)?)
+    }
+
+    /// Gets the id of the channel given in a channel option.
+    pub fn option_channel(&self, name: &str) -> Option<&Snowflake> {
+        match self.option(name)? {
+            CommandOptionValue::Channel(id) => Some(id),
+            _ => None,
         }
     }
 
-    /// Sets the `id` of the modal.
-    pub fn id(mut self, id: &str) -> Self {
-        self.id = id.to_string();
-        self
+    /// Gets the id of the role given in a role option.
+    pub fn option_role(&self, name: &str) -> Option<&Snowflake> {
+        match self.option(name)? {
+            CommandOptionValue::Role(id) => Some(id),
+            _ => None,
+        }
     }
 
-    /// Sets the `title` of the modal.
-    pub fn title(mut self, title: &str) -> Self {
-        self.title = title.to_string();
-        self
+    /// Gets the full attachment object for the file given in an attachment option.
+    pub fn option_resolved_attachment(&self, name: &str) -> Option<&Attachment> {
+        self.resolved.attachments.get(self.option_attachment(name)?)
     }
 
-    /// Adds a field to the modal.
-    pub fn field(mut self, id: &str, label: &str) -> Self {
-        self.fields.push(TextField {
-            id: id.to_string(),
-            label: label.to_string(),
-        });
-        self
+    /// Gets the id of the user or role given in a mentionable option.
+    pub fn option_mentionable(&self, name: &str) -> Option<&Snowflake> {
+        match self.option(name)? {
+            CommandOptionValue::Mentionable(id) => Some(id),
+            _ => None,
+        }
     }
-}
 
-impl From<&discord_types::InteractionRequest> for ApplicationCommand {
-    fn from(req: &discord_types::InteractionRequest) -> Self {
-        ApplicationCommand {
-            command_name: req.data.as_ref().unwrap().name.as_ref().unwrap().clone(),
-            user_id: req.member.as_ref().unwrap().user.id.clone(),
+    /// Gets the id of the attachment given in an attachment option.
+    pub fn option_attachment(&self, name: &str) -> Option<&Snowflake> {
+        match self.option(name)? {
+            CommandOptionValue::Attachment(id) => Some(id),
+            _ => None,
         }
     }
 }
 
-impl From<&discord_types::InteractionRequest> for MessageComponent {
-    fn from(req: &discord_types::InteractionRequest) -> Self {
-        MessageComponent {
-            id: req
-                .data
-                .as_ref()
-                .unwrap()
-                .custom_id
-                .as_ref()
-                .unwrap()
-                .clone(),
+/// Convenience methods for telling slash commands and context menu commands apart, e.g.
+/// `if let Some(cmd) = ac.as_user_command() { ... }`. Each returns `None` for other kinds of
+/// command.
+impl ApplicationCommand {
+    /// Views the command as a slash command, with its options.
+    pub fn as_slash_command(&self) -> Option<SlashCommandView<'_>> {
+        (self.command_type == CommandType::ChatInput).then(|| SlashCommandView {
+            name: &self.command_name,
+            subcommand: self.subcommand.as_ref(),
+            options: &self.options,
+        })
+    }
 
-            source: req.message.as_ref().unwrap().into(),
+    /// Views the command as a user context menu command, with the user it was used on.
+    pub fn as_user_command(&self) -> Option<UserCommandView<'_>> {
+        match &self.target {
+            Some(CommandTarget::User { user, member }) => Some(UserCommandView {
+                name: &self.command_name,
+                user,
+                member: member.as_ref(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Views the command as a message context menu command, with the message it was used on.
+    pub fn as_message_command(&self) -> Option<MessageCommandView<'_>> {
+        match &self.target {
+            Some(CommandTarget::Message(message)) => Some(MessageCommandView {
+                name: &self.command_name,
+                message,
+            }),
+            _ => None,
         }
     }
 }
 
-impl From<&discord_types::InteractionRequest> for ModalSubmit {
-    fn from(req: &discord_types::InteractionRequest) -> Self {
-        ModalSubmit {
+/// Convenience methods for displaying users.
+impl User {
+    /// The name that Discord displays for the user: their display name if they've set one, and
+    /// otherwise their username.
+    pub fn display_name(&self) -> &str {
+        self.global_name.as_deref().unwrap_or(&self.username)
+    }
+
+    /// The URL of the user's avatar image. For users without a custom avatar, this is the URL of
+    /// the default avatar that Discord shows for them.
+    pub fn avatar_url(&self) -> String {
+        match &self.avatar {
+            Some(hash) => {
+                // Animated avatars have hashes prefixed with `a_`.
+                let ext = if hash.starts_with("a_") { "gif" } else { "png" };
+                format!(
+                    "https://cdn.discordapp.com/avatars/{}/{}.{}",
+                    self.id, hash, ext
+                )
+            }
+
+            None => {
+                let index = match self.discriminator.as_str() {
+                    "0" => (self.id.as_str().parse::<u64>().unwrap_or(0) >> 22) % 6,
+                    discriminator => discriminator.parse::<u64>().unwrap_or(0) % 5,
+                };
+                format!("https://cdn.discordapp.com/embed/avatars/{}.png", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "download")]
+impl Attachment {
+    /// Downloads the contents of the attachment. Fails if the attachment has no URL. Requires the
+    /// `download` feature.
+    pub async fn download(&self) -> Result<Vec<u8>, HandlerError> {
+        let url = match &self.url {
+            Some(url) => url,
+            None => return Err(HandlerError::new("Attachment has no URL to download!")),
+        };
+        let res = reqwest::get(url).await?.error_for_status()?;
+        Ok(res.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(feature = "download")]
+impl ApplicationCommand {
+    /// Fetches the invoking user's full profile from Discord's HTTP API, for the parts that
+    /// Discord leaves out of interactions, such as `User::banner`. This costs an API call, which
+    /// counts against the bot's rate limits and takes time out of the 3 seconds Discord allows
+    /// for a response, so it's best kept to the interactions that need it. The user is fetched at
+    /// most once per interaction: later calls return the same user.
+    ///
+    /// Fails with `ApiError::MissingToken` if the runner wasn't given a bot token with
+    /// `Runner::bot_token`. Requires the `download` feature.
+    pub async fn fetch_user(&self) -> Result<&User, ApiError> {
+        if let Some(user) = self.fetched_user.get() {
+            return Ok(user);
+        }
+
+        let bot_token = match self.state.bot_token() {
+            Some(bot_token) => bot_token,
+            None => return Err(ApiError::MissingToken),
+        };
+        let user = super::rest::get_user(bot_token, &self.user_id).await?;
+        Ok(self.fetched_user.get_or_init(|| (&user).into()))
+    }
+}
+
+/// Convenience methods for reading the option being typed.
+impl AutocompleteRequest {
+    /// The name of the option that the user is typing, or `None` if no option is focused.
+    pub fn focused_name(&self) -> Option<&str> {
+        self.focused.as_deref()
+    }
+
+    /// The name of the option that the user is typing, along with what they've typed into it so
+    /// far, or `None` if no option is focused.
+    pub fn focused_option(&self) -> Option<(String, String)> {
+        Some((self.focused.clone()?, self.value.clone()))
+    }
+}
+
+/// Convenience methods for reading select menu choices.
+impl MessageComponent {
+    /// The guild that the component was interacted with in, e.g. for looking up guild-scoped
+    /// state, or `None` if the component was pressed in a DM.
+    pub fn guild_id(&self) -> Option<&Snowflake> {
+        self.guild_id.as_ref()
+    }
+
+    /// The ids of the users, roles or channels that the user chose in a user, role, mentionable
+    /// or channel select menu. Look these up in `resolved` for the full objects.
+    pub fn selected_ids(&self) -> Vec<Snowflake> {
+        self.values.iter().map(|value| value.into()).collect()
+    }
+
+    /// The poll attached to the source message, if any, including the current vote counts.
+    pub fn message_poll(&self) -> Option<&Poll> {
+        self.source.poll.as_ref()
+    }
+
+    /// If this component is the "Try again" button of a message sent by `ModalSubmit::retry`,
+    /// recovers the text the user submitted.
+    pub fn modal_retry(&self) -> Option<ModalRetry> {
+        let state = self.id.strip_prefix(MODAL_RETRY_PREFIX)?;
+        let (modal_id, values) = state.split_once('?').unwrap_or((state, ""));
+
+        let values = values
+            .split('&')
+            .filter_map(|pair| {
+                let (id, value) = pair.split_once('=')?;
+                Some((unescape_retry(id), unescape_retry(value)))
+            })
+            .collect();
+
+        Some(ModalRetry {
+            modal_id: unescape_retry(modal_id),
+            values,
+        })
+    }
+}
+
+/// Escapes the characters that separate the parts of a "Try again" button's custom id.
+fn escape_retry(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('?', "%3F")
+        .replace('&', "%26")
+        .replace('=', "%3D")
+}
+
+/// Reverses `escape_retry`.
+fn unescape_retry(text: &str) -> String {
+    text.replace("%3D", "=")
+        .replace("%26", "&")
+        .replace("%3F", "?")
+        .replace("%25", "%")
+}
+
+/// Convenience methods for reading modal inputs.
+impl ModalSubmit {
+    /// Responds to a submission that failed validation with an ephemeral message listing the
+    /// errors, along with a "Try again" button. When the button is pressed,
+    /// `MessageComponent::modal_retry` recovers the submitted text, so that the modal can be
+    /// reopened with it prefilled.
+    ///
+    /// The submitted text is kept in the button's custom id, which users don't see, as
+    /// `modal_retry:<modal id>?<field id>=<value>&...`, with `%`, `?`, `&` and `=` escaped as in a
+    /// URL. Custom ids are limited to 100 characters, so the shortest values are kept first, and
+    /// fields that don't fit are left empty when the modal is reopened. The modal's id, once
+    /// escaped, must be at most 88 characters, to leave room for the button's prefix; otherwise
+    /// the message fails to convert, and the handler's error is reported instead.
+    pub fn retry(&self, errors: &[&str]) -> Response {
+        let mut text = "Please fix the following, and try again:".to_string();
+        for error in errors {
+            text.push_str("\n- ");
+            text.push_str(error);
+        }
+
+        let mut values = self
+            .values
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(id, value)| format!("{}={}", escape_retry(id), escape_retry(value)))
+            .collect::<Vec<_>>();
+        values.sort_by_key(|pair| (pair.chars().count(), pair.clone()));
+
+        let mut custom_id = format!("{}{}", MODAL_RETRY_PREFIX, escape_retry(&self.id));
+        let mut separator = '?';
+        for pair in values {
+            if custom_id.chars().count() + 1 + pair.chars().count() <= MAX_CUSTOM_ID_LENGTH {
+                custom_id.push(separator);
+                custom_id.push_str(&pair);
+                separator = '&';
+            }
+        }
+
+        Response::Message(
+            Message::new()
+                .text(&text)
+                .ephemeral()
+                .button(&custom_id, "Try again"),
+        )
+    }
+
+    /// Builds a `T` from the modal's inputs. See `FromModal`.
+    pub fn parse<T: FromModal>(&self) -> Result<T, ModalError> {
+        T::from_modal(self)
+    }
+
+    /// Parses the text entered in the field with the given id.
+    pub fn field<T>(&self, id: &str) -> Result<T, ModalError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self
+            .values
+            .get(id)
+            .ok_or_else(|| ModalError::Missing(id.to_string()))?;
+
+        value.parse().map_err(|err: T::Err| ModalError::Invalid {
+            id: id.to_string(),
+            value: value.clone(),
+            reason: err.to_string(),
+        })
+    }
+
+    /// Parses the text entered in the field with the given id, or gives `None` if the field was
+    /// left empty.
+    pub fn optional_field<T>(&self, id: &str) -> Result<Option<T>, ModalError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.values.get(id) {
+            Some(value) if !value.is_empty() => self.field(id).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses the values of the options chosen in the select menu with the given id.
+    pub fn selection<T>(&self, id: &str) -> Result<Vec<T>, ModalError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let values = self
+            .selections
+            .get(id)
+            .ok_or_else(|| ModalError::Missing(id.to_string()))?;
+
+        values
+            .iter()
+            .map(|value| {
+                value.parse().map_err(|err: T::Err| ModalError::Invalid {
+                    id: id.to_string(),
+                    value: value.clone(),
+                    reason: err.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ChannelType {
+    /// Whether the channel is a thread.
+    pub fn is_thread(&self) -> bool {
+        matches!(
+            self,
+            ChannelType::AnnouncementThread
+                | ChannelType::PublicThread
+                | ChannelType::PrivateThread
+        )
+    }
+
+    /// Whether the channel is a DM or group DM.
+    pub fn is_dm(&self) -> bool {
+        matches!(self, ChannelType::Dm | ChannelType::GroupDm)
+    }
+}
+
+impl Default for Message {
+    fn default() -> Self {
+        Message::new()
+    }
+}
+
+/// Convenience methods for building messages.
+impl Message {
+    /// Creates a new message, defaulting to non-ephemeral, and non-editing.
+    pub fn new() -> Self {
+        Message {
+            text: "".to_string(),
+            embeds: Vec::new(),
+            components: Vec::new(),
+            files: Vec::new(),
+            tts: false,
+            poll: None,
+            flags: 0,
+            edit: false,
+            keep: Keep::default(),
+            allowed_mentions: None,
+        }
+    }
+
+    /// Sets the `text` field on the message.
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Adds an embed to the message.
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    /// Adds a button to the message. Button ids must be unique within the message.
+    pub fn button(self, id: &str, text: &str) -> Self {
+        self.add_button(Button::new(id, text))
+    }
+
+    /// Adds a button with the given style to the message, e.g. `ButtonStyle::Danger` for a button
+    /// that deletes something.
+    pub fn styled_button(self, id: &str, text: &str, style: ButtonStyle) -> Self {
+        self.add_button(Button::new(id, text).style(style))
+    }
+
+    /// Adds a button to the message that opens the given URL when clicked. Unlike other buttons,
+    /// link buttons don't spawn interactions.
+    pub fn link_button(self, text: &str, url: &str) -> Self {
+        self.add_button(Button::link(text, url))
+    }
+
+    /// Adds a button to the message that offers the given SKU for purchase, for monetized
+    /// applications. Like link buttons, premium buttons don't spawn interactions.
+    pub fn premium_button(self, sku_id: impl Into<Snowflake>) -> Self {
+        self.add_button(Button::premium(sku_id))
+    }
+
+    /// Adds a button built with `Button`'s convenience methods to the message, for buttons that
+    /// need more than an id and text, e.g. an emoji. Buttons are laid out in rows automatically:
+    /// the button joins the message's last row if that row holds fewer than 5 buttons, and starts
+    /// a new row otherwise.
+    pub fn add_button(mut self, button: Button) -> Self {
+        match self.components.last_mut() {
+            Some(TopLevelComponent::ActionRow(row))
+                if row.len() < 5 && row.iter().all(|c| matches!(c, RowComponent::Button(_))) =>
+            {
+                row.push(button.into())
+            }
+            _ => self
+                .components
+                .push(TopLevelComponent::ActionRow(vec![button.into()])),
+        }
+        self
+    }
+
+    /// Adds a select menu to the message, in its own row. Like button ids, select menu ids must be
+    /// unique within the message.
+    pub fn select(self, select: SelectMenu) -> Self {
+        self.row(vec![select.into()])
+    }
+
+    /// Adds a row of components to the message, e.g.
+    /// `.row(vec![Button::new("yes", "Yes").into(), Button::new("no", "No").into()])`.
+    /// A message without a layout can have at most 5 rows in total.
+    pub fn row(mut self, components: Vec<RowComponent>) -> Self {
+        self.components
+            .push(TopLevelComponent::ActionRow(components));
+        self
+    }
+
+    /// Adds the rows of a `Layout` to the message.
+    pub fn rows(mut self, layout: Layout) -> Self {
+        self.components
+            .extend(layout.rows.into_iter().map(TopLevelComponent::ActionRow));
+        self
+    }
+
+    /// Uploads a file with the message.
+    pub fn file(mut self, file: FileUpload) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    /// Adds a component to the message's layout, making the message a layout message: see
+    /// `LayoutComponent`. Layout messages can't have text, embeds or a poll.
+    pub fn component(mut self, component: LayoutComponent) -> Self {
+        self.components.push(TopLevelComponent::Layout(component));
+        self
+    }
+
+    /// Attaches a poll to the message.
+    pub fn poll(mut self, poll: Poll) -> Self {
+        self.poll = Some(poll);
+        self
+    }
+
+    /// Sets the message to be read aloud by text-to-speech.
+    pub fn tts(mut self) -> Self {
+        self.tts = true;
+        self
+    }
+
+    /// Sets the message to be ephemeral.
+    pub fn ephemeral(self) -> Self {
+        self.flags(message_flags::EPHEMERAL)
+    }
+
+    /// Hides the embeds that Discord would otherwise generate for links in the message's text.
+    pub fn suppress_embeds(self) -> Self {
+        self.flags(message_flags::SUPPRESS_EMBEDS)
+    }
+
+    /// Sends the message silently, without notifying anyone (as with Discord's `@silent`
+    /// prefix). Mentions are still highlighted.
+    pub fn silent(self) -> Self {
+        self.flags(message_flags::SUPPRESS_NOTIFICATIONS)
+    }
+
+    /// Sets the given flags on the message, in addition to any already set. See the
+    /// `message_flags` module for the individual flags.
+    pub fn flags(mut self, flags: u64) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Sets the message to edit the discord message that spawned it. By default, the edit
+    /// replaces the original message's text, embeds and components, clearing any that the edit
+    /// doesn't set; use `keep_text`, `keep_embeds` and `keep_components` to leave them as they are.
+    pub fn edit(mut self) -> Self {
+        self.edit = true;
+        self
+    }
+
+    /// Sets an edit to keep the original message's text.
+    pub fn keep_text(mut self) -> Self {
+        self.keep.text = true;
+        self
+    }
+
+    /// Sets an edit to keep the original message's embeds.
+    pub fn keep_embeds(mut self) -> Self {
+        self.keep.embeds = true;
+        self
+    }
+
+    /// Sets an edit to keep the original message's buttons, select menus and layout components.
+    pub fn keep_components(mut self) -> Self {
+        self.keep.components = true;
+        self
+    }
+
+    /// Sets an edit to clear the original message's text, dropping any text set so far.
+    pub fn clear_text(mut self) -> Self {
+        self.text.clear();
+        self.keep.text = false;
+        self
+    }
+
+    /// Sets an edit to clear the original message's embeds, dropping any embeds added so far.
+    pub fn clear_embeds(mut self) -> Self {
+        self.embeds.clear();
+        self.keep.embeds = false;
+        self
+    }
+
+    /// Sets an edit to clear the original message's buttons, select menus and layout components,
+    /// dropping any added so far.
+    pub fn clear_components(mut self) -> Self {
+        self.components.clear();
+        self.keep.components = false;
+        self
+    }
+
+    /// Sets which mentions in the message will notify the mentioned users and roles.
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
+    /// Allows user mentions in the message to notify the mentioned users. Role, `@everyone` and
+    /// `@here` mentions still won't notify anyone.
+    pub fn ping_users(self) -> Self {
+        self.allowed_mentions(AllowedMentions::none().users())
+    }
+
+    /// Whether the message is a layout message, with layout components or the `IS_COMPONENTS_V2`
+    /// flag, rather than text, embeds and rows of components.
+    pub fn is_layout(&self) -> bool {
+        self.flags & message_flags::IS_COMPONENTS_V2 != 0
+            || self
+                .components
+                .iter()
+                .any(|c| matches!(c, TopLevelComponent::Layout(_)))
+    }
+
+    /// Creates an ephemeral message reporting an error, as a red embed with a standard title and
+    /// the given text. See `Embed::error` to change the title or color.
+    pub fn error(text: &str) -> Self {
+        Message::new().embed(Embed::error(text)).ephemeral()
+    }
+
+    /// Creates an ephemeral message confirming that something succeeded, as a green embed with a
+    /// standard title and the given text. See `Embed::success` to change the title or color.
+    pub fn success(text: &str) -> Self {
+        Message::new().embed(Embed::success(text)).ephemeral()
+    }
+}
+
+/// Convenience methods for building layouts.
+impl Layout {
+    /// Creates a new layout with no rows.
+    pub fn new() -> Self {
+        Layout::default()
+    }
+
+    /// Adds a row, built by the given function. A row must hold either 1 to 5 buttons, or a single
+    /// select menu; a message with an invalid row fails to convert into a response.
+    pub fn row<F>(mut self, build: F) -> Self
+    where
+        F: FnOnce(Row) -> Row,
+    {
+        let row = build(Row::default());
+        self.rows.push(row.components);
+        self
+    }
+}
+
+/// Convenience methods for building rows.
+impl Row {
+    /// Adds a button to the row.
+    pub fn button(mut self, button: Button) -> Self {
+        self.components.push(RowComponent::Button(button));
+        self
+    }
+
+    /// Adds a select menu to the row.
+    pub fn select(mut self, select: SelectMenu) -> Self {
+        self.components.push(RowComponent::Select(select));
+        self
+    }
+}
+
+/// Convenience methods for building select menus.
+impl SelectMenu {
+    /// Creates a new string select menu with the given id and no options, from which the user
+    /// must choose exactly one option.
+    pub fn new(id: &str) -> Self {
+        Self::of_type(id, SelectMenuType::String)
+    }
+
+    /// Creates a new select menu of users, from which the user must choose exactly one.
+    pub fn users(id: &str) -> Self {
+        Self::of_type(id, SelectMenuType::User)
+    }
+
+    /// Creates a new select menu of roles, from which the user must choose exactly one.
+    pub fn roles(id: &str) -> Self {
+        Self::of_type(id, SelectMenuType::Role)
+    }
+
+    /// Creates a new select menu of users and roles, from which the user must choose exactly one.
+    pub fn mentionables(id: &str) -> Self {
+        Self::of_type(id, SelectMenuType::Mentionable)
+    }
+
+    /// Creates a new select menu of channels, from which the user must choose exactly one.
+    pub fn channels(id: &str) -> Self {
+        Self::of_type(id, SelectMenuType::Channel)
+    }
+
+    fn of_type(id: &str, menu_type: SelectMenuType) -> Self {
+        SelectMenu {
+            id: id.to_string(),
+            menu_type,
+            placeholder: None,
+            options: Vec::new(),
+            min_values: 1,
+            max_values: 1,
+            disabled: false,
+        }
+    }
+
+    /// Sets the text shown when no option is chosen.
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    /// Adds an option to the select menu.
+    pub fn option(mut self, option: SelectOption) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Sets the range of how many options the user can choose.
+    pub fn values(mut self, min: u8, max: u8) -> Self {
+        self.min_values = min;
+        self.max_values = max;
+        self
+    }
+
+    /// Sets the select menu to be disabled, e.g. when editing a message after a choice has been
+    /// made.
+    pub fn disabled(mut self) -> Self {
+        self.disabled = true;
+        self
+    }
+}
+
+/// Convenience methods for building select options.
+impl SelectOption {
+    /// Creates a new option with the given label and value.
+    pub fn new(label: &str, value: &str) -> Self {
+        SelectOption {
+            label: label.to_string(),
+            value: value.to_string(),
+            description: None,
+            default: false,
+            emoji: None,
+        }
+    }
+
+    /// Sets the `description` of the option, shown below its label.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets whether the option is chosen by default.
+    pub fn default(mut self, default: bool) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Sets the emoji displayed before the option's label.
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = Some(emoji);
+        self
+    }
+}
+
+/// Convenience methods for building buttons.
+impl Button {
+    /// Creates a new primary button with the given id and text.
+    pub fn new(id: &str, text: &str) -> Self {
+        Button {
+            id: id.to_string(),
+            text: text.to_string(),
+            style: ButtonStyle::Primary,
+            url: None,
+            sku_id: None,
+            emoji: None,
+            disabled: false,
+        }
+    }
+
+    /// Creates a new link button, which opens the given URL when clicked.
+    pub fn link(text: &str, url: &str) -> Self {
+        Button {
+            id: String::new(),
+            text: text.to_string(),
+            style: ButtonStyle::Link,
+            url: Some(url.to_string()),
+            sku_id: None,
+            emoji: None,
+            disabled: false,
+        }
+    }
+
+    /// Creates a new premium button, which offers the given SKU for purchase.
+    pub fn premium(sku_id: impl Into<Snowflake>) -> Self {
+        Button {
+            id: String::new(),
+            text: String::new(),
+            style: ButtonStyle::Premium,
+            url: None,
+            sku_id: Some(sku_id.into()),
+            emoji: None,
+            disabled: false,
+        }
+    }
+
+    /// Sets the `style` of the button. Link and premium buttons have their own constructors, and
+    /// can't be restyled, so the call is ignored for them, as is setting either style here.
+    pub fn style(mut self, style: ButtonStyle) -> Self {
+        let special = |style| matches!(style, ButtonStyle::Link | ButtonStyle::Premium);
+        if !special(style) && !special(self.style) {
+            self.style = style;
+        }
+        self
+    }
+
+    /// Sets the emoji displayed before the button's text.
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = Some(emoji);
+        self
+    }
+
+    /// Sets the button to be disabled, e.g. when editing a message after the button has been
+    /// clicked.
+    pub fn disabled(mut self) -> Self {
+        self.disabled = true;
+        self
+    }
+}
+
+/// Convenience methods for creating emoji.
+impl Emoji {
+    /// A standard emoji, e.g. `Emoji::unicode("👍")`.
+    pub fn unicode(emoji: &str) -> Self {
+        Emoji::Unicode(emoji.to_string())
+    }
+
+    /// A custom emoji, with the given id and name.
+    pub fn custom(id: impl Into<Snowflake>, name: &str) -> Self {
+        Emoji::Custom {
+            id: id.into(),
+            name: name.to_string(),
+            animated: false,
+        }
+    }
+
+    /// An animated custom emoji, with the given id and name.
+    pub fn animated(id: impl Into<Snowflake>, name: &str) -> Self {
+        Emoji::Custom {
+            id: id.into(),
+            name: name.to_string(),
+            animated: true,
+        }
+    }
+}
+
+/// Convenience methods for building file uploads.
+impl FileUpload {
+    /// Creates a new file upload with the given filename and contents.
+    pub fn new(filename: &str, data: Vec<u8>) -> Self {
+        FileUpload {
+            filename: filename.to_string(),
+            data,
+            description: None,
+        }
+    }
+
+    /// Sets the file's alt text, describing an image for screen readers. Discord allows up to 1024
+    /// characters.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+/// Convenience methods for building polls.
+impl Poll {
+    /// Creates a new poll with the given question and no answers, which accepts a single vote per
+    /// user for 24 hours.
+    pub fn new(question: &str) -> Self {
+        Poll {
+            question: question.to_string(),
+            answers: Vec::new(),
+            duration: 24,
+            allow_multiselect: false,
+            expiry: None,
+            results: None,
+        }
+    }
+
+    /// Adds an answer to the poll.
+    pub fn answer(mut self, answer: PollAnswer) -> Self {
+        self.answers.push(answer);
+        self
+    }
+
+    /// Sets how long the poll accepts votes, in hours.
+    pub fn duration(mut self, hours: u32) -> Self {
+        self.duration = hours;
+        self
+    }
+
+    /// Allows users to choose more than one answer.
+    pub fn multiselect(mut self) -> Self {
+        self.allow_multiselect = true;
+        self
+    }
+
+    /// The number of votes for the given answer, if the poll has results.
+    pub fn votes(&self, answer_id: u32) -> Option<u32> {
+        let results = self.results.as_ref()?;
+        Some(results.counts.get(&answer_id).copied().unwrap_or(0))
+    }
+}
+
+/// Convenience methods for building poll answers.
+impl PollAnswer {
+    /// Creates a new answer with the given text.
+    pub fn new(text: &str) -> Self {
+        PollAnswer {
+            id: 0,
+            text: text.to_string(),
+            emoji: None,
+        }
+    }
+
+    /// Sets the emoji displayed before the answer's text.
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = Some(emoji);
+        self
+    }
+}
+
+impl Followup {
+    /// Creates a new followup that sends the given message.
+    pub fn new(message: Message) -> Self {
+        Followup { message }
+    }
+
+    /// Sets the followup to be ephemeral, independently of the initial response's visibility.
+    pub fn ephemeral(mut self) -> Self {
+        self.message = self.message.ephemeral();
+        self
+    }
+}
+
+#[cfg(feature = "download")]
+impl Followup {
+    /// Sends the followup for the interaction with the given application id and token, e.g. from
+    /// work that continues after the handler has responded. As with a `Runner`'s responses, the
+    /// message mentions no one unless it sets its own allowed mentions. Requires the `download`
+    /// feature.
+    pub async fn send(self, application_id: &Snowflake, token: &str) -> Result<(), HandlerError> {
+        let mut res = discord_types::InteractionResponse::try_from(self)?;
+        super::auth::suppress_mentions(&mut res);
+        super::rest::create_followup(application_id, token, &res).await
+    }
+}
+
+/// Convenience methods for building responses.
+impl Response {
+    /// Defers the response, as with `Response::Deferred`, so that both the "thinking..." state and
+    /// the eventual message are visible to only the user. This is the usual response for slow
+    /// commands with private results. The message that then replaces the original response keeps
+    /// its ephemeral visibility, whatever flags it's sent with.
+    pub fn defer_ephemeral() -> Self {
+        Response::Deferred { ephemeral: true }
+    }
+
+    /// Updates the message that the component is attached to, and then sends the given followup
+    /// message visible to only the user, as with `Response::UpdateWithFollowup`. Requires the
+    /// `download` feature.
+    #[cfg(feature = "download")]
+    pub fn update_with_ephemeral_followup(update: Message, followup: Message) -> Self {
+        Response::UpdateWithFollowup {
+            update,
+            followup: Followup::new(followup).ephemeral(),
+        }
+    }
+}
+
+/// Convenience methods for building choices.
+impl Choice {
+    /// Creates a new choice for a string option.
+    pub fn string(name: &str, value: &str) -> Self {
+        Self::new(name, ChoiceValue::String(value.to_string()))
+    }
+
+    /// Creates a new choice for an integer option.
+    pub fn int(name: &str, value: i64) -> Self {
+        Self::new(name, ChoiceValue::Integer(value))
+    }
+
+    /// Creates a new choice for a number option.
+    pub fn number(name: &str, value: f64) -> Self {
+        Self::new(name, ChoiceValue::Number(value))
+    }
+
+    fn new(name: &str, value: ChoiceValue) -> Self {
+        Choice {
+            name: name.to_string(),
+            name_localizations: HashMap::new(),
+            value,
+        }
+    }
+
+    /// Adds a translation of the choice's name, shown to users with the given locale.
+    pub fn localize(mut self, locale: &str, name: &str) -> Self {
+        self.name_localizations
+            .insert(locale.to_string(), name.to_string());
+        self
+    }
+}
+
+/// Convenience methods for building layout components.
+impl LayoutComponent {
+    /// Creates a piece of markdown text.
+    pub fn text(content: &str) -> Self {
+        LayoutComponent::Text(content.to_string())
+    }
+
+    /// Creates a small separator with a divider line.
+    pub fn separator() -> Self {
+        LayoutComponent::Separator {
+            divider: true,
+            large: false,
+        }
+    }
+
+    /// Creates a download for the uploaded file with the given filename.
+    pub fn file(filename: &str) -> Self {
+        LayoutComponent::File {
+            filename: filename.to_string(),
+            spoiler: false,
+        }
+    }
+}
+
+/// Convenience methods for building sections.
+impl Section {
+    /// Creates a new section with the given accessory and no text.
+    pub fn new(accessory: SectionAccessory) -> Self {
+        Section {
+            texts: Vec::new(),
+            accessory,
+        }
+    }
+
+    /// Adds a piece of markdown text to the section.
+    pub fn text(mut self, content: &str) -> Self {
+        self.texts.push(content.to_string());
+        self
+    }
+}
+
+/// Convenience methods for building media items.
+impl MediaItem {
+    /// Creates a new media item with the given URL.
+    pub fn new(url: &str) -> Self {
+        MediaItem {
+            url: url.to_string(),
+            description: None,
+            spoiler: false,
+        }
+    }
+
+    /// Sets the alt text for the media.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the media to be blurred until the user clicks it.
+    pub fn spoiler(mut self) -> Self {
+        self.spoiler = true;
+        self
+    }
+}
+
+impl Default for Container {
+    fn default() -> Self {
+        Container::new()
+    }
+}
+
+/// Convenience methods for building containers.
+impl Container {
+    /// Creates a new, empty container.
+    pub fn new() -> Self {
+        Container {
+            components: Vec::new(),
+            accent_color: None,
+            spoiler: false,
+        }
+    }
+
+    /// Adds a component to the container.
+    pub fn component(mut self, component: LayoutComponent) -> Self {
+        self.components.push(component);
+        self
+    }
+
+    /// Sets the color of the container's left border, as an RGB integer.
+    pub fn accent_color(mut self, color: u32) -> Self {
+        self.accent_color = Some(color);
+        self
+    }
+
+    /// Sets the container's contents to be blurred until the user clicks them.
+    pub fn spoiler(mut self) -> Self {
+        self.spoiler = true;
+        self
+    }
+}
+
+impl Default for Embed {
+    fn default() -> Self {
+        Embed::new()
+    }
+}
+
+/// Convenience methods for building embeds.
+impl Embed {
+    /// Creates a new, empty embed.
+    pub fn new() -> Self {
+        Embed {
+            title: None,
+            description: None,
+            url: None,
+            timestamp: None,
+            color: None,
+            footer: None,
+            image: None,
+            thumbnail: None,
+            author: None,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Creates a red embed with the title "⚠️ Error" and the given text, as sent by
+    /// `Message::error`. The title and color can be overridden as usual, e.g.
+    /// `Embed::error(text).title("⚠️ Not allowed")`.
+    pub fn error(text: &str) -> Self {
+        Embed::new()
+            .title("⚠️ Error")
+            .description(text)
+            .color(ERROR_COLOR)
+    }
+
+    /// Creates a green embed with the title "✅ Success" and the given text, as sent by
+    /// `Message::success`. The title and color can be overridden as with `error`.
+    pub fn success(text: &str) -> Self {
+        Embed::new()
+            .title("✅ Success")
+            .description(text)
+            .color(SUCCESS_COLOR)
+    }
+
+    /// Sets the `title` of the embed.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the `description` of the embed, displayed below its title.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the URL that the embed's title links to.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// Sets the timestamp displayed in the embed's footer, in ISO 8601 format.
+    pub fn timestamp(mut self, timestamp: &str) -> Self {
+        self.timestamp = Some(timestamp.to_string());
+        self
+    }
+
+    /// Sets the color of the embed's left border, as an RGB integer (e.g. `0x5865F2`).
+    pub fn color(mut self, color: u32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the text of the embed's footer.
+    pub fn footer(mut self, text: &str) -> Self {
+        self.footer = Some(EmbedFooter {
+            text: text.to_string(),
+            icon_url: None,
+        });
+        self
+    }
+
+    /// Sets the URL of the embed's image.
+    pub fn image(mut self, url: &str) -> Self {
+        self.image = Some(url.to_string());
+        self
+    }
+
+    /// Sets the URL of the embed's thumbnail.
+    pub fn thumbnail(mut self, url: &str) -> Self {
+        self.thumbnail = Some(url.to_string());
+        self
+    }
+
+    /// Sets the name of the embed's author.
+    pub fn author(mut self, name: &str) -> Self {
+        self.author = Some(EmbedAuthor {
+            name: name.to_string(),
+            url: None,
+            icon_url: None,
+        });
+        self
+    }
+
+    /// Adds a field to the embed. Inline fields may be displayed side by side.
+    pub fn field(mut self, name: &str, value: &str, inline: bool) -> Self {
+        self.fields.push(EmbedField {
+            name: name.to_string(),
+            value: value.to_string(),
+            inline,
+        });
+        self
+    }
+}
+
+/// Convenience methods for building allowed mentions.
+impl AllowedMentions {
+    /// Creates a new set of allowed mentions, under which no mentions notify anyone.
+    pub fn none() -> Self {
+        AllowedMentions {
+            users: false,
+            roles: false,
+            everyone: false,
+            user_ids: Vec::new(),
+            role_ids: Vec::new(),
+            replied_user: false,
+        }
+    }
+
+    /// Allows user mentions to notify the mentioned users.
+    pub fn users(mut self) -> Self {
+        self.users = true;
+        self
+    }
+
+    /// Allows role mentions to notify the mentioned roles.
+    pub fn roles(mut self) -> Self {
+        self.roles = true;
+        self
+    }
+
+    /// Allows `@everyone` and `@here` to notify the channel.
+    pub fn everyone(mut self) -> Self {
+        self.everyone = true;
+        self
+    }
+
+    /// Allows mentions of the given user to notify them. This can't be combined with `users`,
+    /// which already allows all user mentions.
+    pub fn user(mut self, id: impl Into<Snowflake>) -> Self {
+        self.user_ids.push(id.into());
+        self
+    }
+
+    /// Allows mentions of the given role to notify it. This can't be combined with `roles`,
+    /// which already allows all role mentions.
+    pub fn role(mut self, id: impl Into<Snowflake>) -> Self {
+        self.role_ids.push(id.into());
+        self
+    }
+
+    /// Allows a reply to notify the author of the message being replied to.
+    pub fn replied_user(mut self) -> Self {
+        self.replied_user = true;
+        self
+    }
+}
+
+impl Modal<(), ()> {
+    /// Creates a new modal, without an id or title.
+    pub fn new() -> Self {
+        Modal {
+            id: (),
+            title: (),
+            fields: Vec::new(),
+        }
+    }
+}
+
+impl Default for Modal<(), ()> {
+    fn default() -> Self {
+        Modal::new()
+    }
+}
+
+/// Convenience methods for building modals.
+impl<I, T> Modal<I, T> {
+    /// Sets the `id` of the modal.
+    pub fn id(self, id: &str) -> Modal<String, T> {
+        Modal {
+            id: id.to_string(),
+            title: self.title,
+            fields: self.fields,
+        }
+    }
+
+    /// Sets the `title` of the modal.
+    pub fn title(self, title: &str) -> Modal<I, String> {
+        Modal {
+            id: self.id,
+            title: title.to_string(),
+            fields: self.fields,
+        }
+    }
+
+    /// Adds a field to the modal.
+    pub fn field(mut self, id: &str, label: &str) -> Self {
+        self.fields.push(ModalField::new(id, label).into());
+        self
+    }
+
+    /// Adds a paragraph field to the modal, for multi-line input.
+    pub fn paragraph_field(mut self, id: &str, label: &str) -> Self {
+        self.fields.push(ModalField::paragraph(id, label).into());
+        self
+    }
+
+    /// Adds a field built with `ModalField`'s convenience methods to the modal. Fields are shown
+    /// in the order they're added.
+    pub fn add_field(mut self, field: ModalField) -> Self {
+        self.fields.push(field.into());
+        self
+    }
+
+    /// Inserts a field built with `ModalField`'s convenience methods at the given position in the
+    /// modal, shifting the fields after it down.
+    pub fn insert_field(mut self, index: usize, field: ModalField) -> Self {
+        self.fields.insert(index, field.into());
+        self
+    }
+
+    /// Adds a select menu to the modal, shown under the given label. The options that the user
+    /// chooses are submitted in `ModalSubmit::selections`. The modal can be submitted without a
+    /// choice if the select menu's minimum number of values is 0.
+    pub fn select(mut self, label: &str, select: SelectMenu) -> Self {
+        self.fields.push(ModalComponent::Select {
+            label: label.to_string(),
+            select,
+        });
+        self
+    }
+
+    /// Sets the text shown when the most recently added field is empty.
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.last_field("placeholder").placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    /// Sets the minimum number of characters the user must enter in the most recently added field.
+    pub fn min_length(mut self, min_length: u16) -> Self {
+        self.last_field("min_length").min_length = Some(min_length);
+        self
+    }
+
+    /// Sets the maximum number of characters the user can enter in the most recently added field.
+    pub fn max_length(mut self, max_length: u16) -> Self {
+        self.last_field("max_length").max_length = Some(max_length);
+        self
+    }
+
+    /// Allows the modal to be submitted with the most recently added field left empty.
+    pub fn optional(mut self) -> Self {
+        self.last_field("optional").required = false;
+        self
+    }
+
+    /// Prefills the most recently added field with the given text, e.g. to let the user edit a
+    /// previous submission.
+    pub fn value(mut self, value: &str) -> Self {
+        self.last_field("value").value = Some(value.to_string());
+        self
+    }
+
+    /// Prefills the modal's text fields with the given values, keyed by the fields' ids, e.g. the
+    /// values recovered by `MessageComponent::modal_retry`.
+    pub fn prefill(mut self, values: &HashMap<String, String>) -> Self {
+        for field in &mut self.fields {
+            if let ModalComponent::Text(field) = field {
+                if let Some(value) = values.get(&field.id) {
+                    field.value = Some(value.clone());
+                }
+            }
+        }
+        self
+    }
+
+    fn last_field(&mut self, setting: &str) -> &mut ModalField {
+        match self.fields.last_mut() {
+            Some(ModalComponent::Text(field)) => field,
+            Some(ModalComponent::Select { .. }) => {
+                panic!(
+                    "Modal's last field must be a text field to set {}!",
+                    setting
+                )
+            }
+            None => panic!("Modal must have a field before setting {}!", setting),
+        }
+    }
+}
+
+impl From<ModalField> for ModalComponent {
+    fn from(field: ModalField) -> Self {
+        ModalComponent::Text(field)
+    }
+}
+
+/// Convenience methods for building modal fields.
+impl ModalField {
+    /// Creates a single-line text field.
+    pub fn new(id: &str, label: &str) -> Self {
+        ModalField {
+            id: id.to_string(),
+            label: label.to_string(),
+            style: TextInputStyle::Short,
+            placeholder: None,
+            min_length: None,
+            max_length: None,
+            required: true,
+            value: None,
+        }
+    }
+
+    /// Creates a paragraph field, for multi-line input.
+    pub fn paragraph(id: &str, label: &str) -> Self {
+        ModalField {
+            style: TextInputStyle::Paragraph,
+            ..ModalField::new(id, label)
+        }
+    }
+
+    /// Sets the size of the field.
+    pub fn style(mut self, style: TextInputStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the text shown when the field is empty.
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+
+    /// Sets the range of how many characters the user can enter.
+    pub fn length(mut self, min: u16, max: u16) -> Self {
+        self.min_length = Some(min);
+        self.max_length = Some(max);
+        self
+    }
+
+    /// Sets the minimum number of characters the user must enter.
+    pub fn min_length(mut self, min_length: u16) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Sets the maximum number of characters the user can enter.
+    pub fn max_length(mut self, max_length: u16) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Allows the modal to be submitted with the field left empty.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Prefills the field with the given text.
+    pub fn value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+}
+
+impl From<&discord_types::InteractionRequest> for ApplicationCommand {
+    fn from(req: &discord_types::InteractionRequest) -> Self {
+        let data = req.data.as_ref().unwrap();
+        let (subcommand, options) = find_subcommand(data.options.as_deref().unwrap_or(&[]));
+        let command_type = data.r#type.map_or(CommandType::ChatInput, |t| t.into());
+
+        ApplicationCommand {
+            command_id: data.id.clone(),
+            command_name: data.name.as_ref().unwrap().clone(),
+            command_type,
+            user_id: req.invoking_user().unwrap().id.clone(),
+            user: req.invoking_user().unwrap().into(),
+            member: req.member.as_ref().map(|member| member.into()),
+            guild_id: req.guild_id.clone(),
+            channel_id: req.channel_id.clone(),
+            channel: req.channel.as_ref().map(|channel| channel.into()),
+            interaction_id: req.id.clone(),
+            token: req.token.clone(),
+            application_id: req.application_id.clone(),
+            locale: req.locale.clone(),
+            guild_locale: req.guild_locale.clone(),
+            app_permissions: req
+                .app_permissions
+                .as_ref()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(0),
+            context: req.context.and_then(|c| c.try_into().ok()),
+            authorizing_owners: req.authorizing_integration_owners.as_ref().into(),
+            entitlements: req.entitlements.iter().map(|e| e.into()).collect(),
+            subcommand,
+            options: options
+                .iter()
+                .filter_map(|opt| opt.try_into().ok())
+                .collect(),
+            resolved: data.resolved.as_ref().into(),
+            target_id: data.target_id.clone(),
+            target: data
+                .target_id
+                .as_ref()
+                .and_then(|id| command_target(command_type, id, data.resolved.as_ref()?)),
+            timed_out_until: req
+                .member
+                .as_ref()
+                .and_then(|member| member.communication_disabled_until.clone()),
+            raw_data: req.raw_data.clone(),
+            state: State::default(),
+            #[cfg(feature = "download")]
+            fetched_user: OnceLock::new(),
+        }
+    }
+}
+
+impl From<discord_types::ApplicationCommandType> for CommandType {
+    fn from(t: discord_types::ApplicationCommandType) -> Self {
+        use discord_types::ApplicationCommandType as T;
+
+        match t {
+            T::ChatInput => CommandType::ChatInput,
+            T::User => CommandType::User,
+            T::Message => CommandType::Message,
+            T::PrimaryEntryPoint => CommandType::PrimaryEntryPoint,
+        }
+    }
+}
+
+/// Finds the target of a context menu command among the resolved data.
+fn command_target(
+    command_type: CommandType,
+    target_id: &Snowflake,
+    resolved: &discord_types::ResolvedData,
+) -> Option<CommandTarget> {
+    match command_type {
+        CommandType::User => Some(CommandTarget::User {
+            user: resolved.users.as_ref()?.get(target_id)?.into(),
+            member: resolved
+                .members
+                .as_ref()
+                .and_then(|members| members.get(target_id))
+                .map(|member| member.into()),
+        }),
+        CommandType::Message => Some(CommandTarget::Message(
+            resolved.messages.as_ref()?.get(target_id)?.into(),
+        )),
+        _ => None,
+    }
+}
+
+impl From<&discord_types::InteractionRequest> for AutocompleteRequest {
+    fn from(req: &discord_types::InteractionRequest) -> Self {
+        let (_, options) =
+            find_subcommand(req.data.as_ref().unwrap().options.as_deref().unwrap_or(&[]));
+        let focused = options.iter().find(|opt| opt.focused == Some(true));
+
+        AutocompleteRequest {
+            command: req.into(),
+            focused: focused.map(|opt| opt.name.clone()),
+            value: match focused.and_then(|opt| opt.value.as_ref()) {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => String::new(),
+            },
+        }
+    }
+}
+
+/// Finds the subcommand (if any) in a command's top level options, returning it along with the
+/// options that were passed to it.
+fn find_subcommand(
+    options: &[discord_types::ApplicationCommandOption],
+) -> (
+    Option<Subcommand>,
+    &[discord_types::ApplicationCommandOption],
+) {
+    use discord_types::ApplicationCommandOptionType as T;
+
+    match options.first() {
+        Some(group) if group.r#type == T::SubCommandGroup => {
+            // A group is always sent with the subcommand that was invoked in it.
+            let sub = match group.options.as_deref().and_then(|subs| subs.first()) {
+                Some(sub) => sub,
+                None => return (None, &[]),
+            };
+            let subcommand = Subcommand {
+                group: Some(group.name.clone()),
+                name: sub.name.clone(),
+            };
+            (Some(subcommand), sub.options.as_deref().unwrap_or(&[]))
+        }
+
+        Some(sub) if sub.r#type == T::SubCommand => {
+            let subcommand = Subcommand {
+                group: None,
+                name: sub.name.clone(),
+            };
+            (Some(subcommand), sub.options.as_deref().unwrap_or(&[]))
+        }
+
+        _ => (None, options),
+    }
+}
+
+impl TryFrom<&discord_types::ApplicationCommandOption> for CommandOption {
+    type Error = ();
+
+    /// Converts a valued command option. Fails for subcommands and subcommand groups, which
+    /// have nested options instead of a value.
+    fn try_from(opt: &discord_types::ApplicationCommandOption) -> Result<Self, ()> {
+        use discord_types::ApplicationCommandOptionType as T;
+
+        let value = opt.value.as_ref().ok_or(())?;
+        let string = || value.as_str().map(|s| s.to_string()).ok_or(());
+
+        let value = match opt.r#type {
+            T::SubCommand | T::SubCommandGroup => return Err(()),
+            T::String => CommandOptionValue::String(string()?),
+            T::Integer => CommandOptionValue::Integer(value.as_i64().ok_or(())?),
+            T::Boolean => CommandOptionValue::Boolean(value.as_bool().ok_or(())?),
+            T::User => CommandOptionValue::User(string()?.into()),
+            T::Channel => CommandOptionValue::Channel(string()?.into()),
+            T::Role => CommandOptionValue::Role(string()?.into()),
+            T::Mentionable => CommandOptionValue::Mentionable(string()?.into()),
+            T::Number => CommandOptionValue::Number(value.as_f64().ok_or(())?),
+            T::Attachment => CommandOptionValue::Attachment(string()?.into()),
+        };
+
+        Ok(CommandOption {
+            name: opt.name.clone(),
+            value,
+        })
+    }
+}
+
+impl From<&discord_types::InteractionRequest> for MessageComponent {
+    fn from(req: &discord_types::InteractionRequest) -> Self {
+        MessageComponent {
+            id: req
+                .data
+                .as_ref()
+                .unwrap()
+                .custom_id
+                .as_ref()
+                .unwrap()
+                .clone(),
+
+            user_id: req.invoking_user().unwrap().id.clone(),
+            values: req
+                .data
+                .as_ref()
+                .unwrap()
+                .values
+                .clone()
+                .unwrap_or_default(),
+            resolved: req.data.as_ref().unwrap().resolved.as_ref().into(),
+            user: req.invoking_user().unwrap().into(),
+            member: req.member.as_ref().map(|member| member.into()),
+
+            guild_id: req.guild_id.clone(),
+            channel_id: req.channel_id.clone(),
+            channel: req.channel.as_ref().map(|channel| channel.into()),
+            interaction_id: req.id.clone(),
+            token: req.token.clone(),
+            application_id: req.application_id.clone(),
+            locale: req.locale.clone(),
+            guild_locale: req.guild_locale.clone(),
+            app_permissions: req
+                .app_permissions
+                .as_ref()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(0),
+            context: req.context.and_then(|c| c.try_into().ok()),
+            authorizing_owners: req.authorizing_integration_owners.as_ref().into(),
+            entitlements: req.entitlements.iter().map(|e| e.into()).collect(),
+
+            source: req.message.as_ref().unwrap().into(),
+            raw_data: req.raw_data.clone(),
+            state: State::default(),
+            params: Params::default(),
+        }
+    }
+}
+
+/// The inputs in a submitted modal. Text fields are submitted wrapped in rows, and select menus
+/// wrapped in labels.
+fn modal_inputs(
+    req: &discord_types::InteractionRequest,
+) -> impl Iterator<Item = &discord_types::Component> {
+    req.data
+        .as_ref()
+        .unwrap()
+        .components
+        .as_deref()
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|c| match &c.component {
+            Some(inner) => Some(inner.as_ref()),
+            None => c.components.as_ref().and_then(|inner| inner.first()),
+        })
+}
+
+impl From<&discord_types::InteractionRequest> for ModalSubmit {
+    fn from(req: &discord_types::InteractionRequest) -> Self {
+        ModalSubmit {
             id: req
                 .data
                 .as_ref()
@@ -180,115 +2769,1975 @@ impl From<&discord_types::InteractionRequest> for ModalSubmit {
                 .unwrap()
                 .clone(),
 
-            values: req
-                .data
-                .as_ref()
-                .unwrap()
-                .components
-                .as_ref()
-                .unwrap()
+            user_id: req.invoking_user().unwrap().id.clone(),
+            user: req.invoking_user().unwrap().into(),
+            member: req.member.as_ref().map(|member| member.into()),
+
+            values: modal_inputs(req)
+                .filter_map(|input| Some((input.custom_id.clone()?, input.value.clone()?)))
+                .collect(),
+            selections: modal_inputs(req)
+                .filter_map(|input| Some((input.custom_id.clone()?, input.values.clone()?)))
+                .collect(),
+            fields: modal_inputs(req)
+                .filter_map(|input| {
+                    let value = match (&input.value, &input.values) {
+                        (Some(value), _) => ModalValue::Text(value.clone()),
+                        (None, Some(values)) => ModalValue::Selection(values.clone()),
+                        (None, None) => return None,
+                    };
+                    Some(ModalInput {
+                        id: input.custom_id.clone()?,
+                        value,
+                    })
+                })
+                .collect(),
+
+            guild_id: req.guild_id.clone(),
+            channel_id: req.channel_id.clone(),
+            channel: req.channel.as_ref().map(|channel| channel.into()),
+            interaction_id: req.id.clone(),
+            token: req.token.clone(),
+            application_id: req.application_id.clone(),
+            locale: req.locale.clone(),
+            guild_locale: req.guild_locale.clone(),
+            app_permissions: req
+                .app_permissions
+                .as_ref()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(0),
+            context: req.context.and_then(|c| c.try_into().ok()),
+            authorizing_owners: req.authorizing_integration_owners.as_ref().into(),
+            entitlements: req.entitlements.iter().map(|e| e.into()).collect(),
+
+            source: req.message.as_ref().map(|message| message.into()),
+            raw_data: req.raw_data.clone(),
+            state: State::default(),
+            params: Params::default(),
+        }
+    }
+}
+
+impl TryFrom<u8> for InteractionContext {
+    type Error = ();
+
+    fn try_from(context: u8) -> Result<Self, ()> {
+        match context {
+            0 => Ok(InteractionContext::Guild),
+            1 => Ok(InteractionContext::BotDm),
+            2 => Ok(InteractionContext::PrivateChannel),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<Option<&HashMap<String, Snowflake>>> for AuthorizingOwners {
+    fn from(owners: Option<&HashMap<String, Snowflake>>) -> Self {
+        // Keyed by installation type: 0 is a guild installation and 1 is a user installation.
+        AuthorizingOwners {
+            guild_install: owners.and_then(|o| o.get("0").cloned()),
+            user_install: owners.and_then(|o| o.get("1").cloned()),
+        }
+    }
+}
+
+impl From<&discord_types::Entitlement> for Entitlement {
+    fn from(entitlement: &discord_types::Entitlement) -> Self {
+        Entitlement {
+            id: entitlement.id.clone(),
+            sku_id: entitlement.sku_id.clone(),
+            user_id: entitlement.user_id.clone(),
+            guild_id: entitlement.guild_id.clone(),
+            entitlement_type: match entitlement.r#type {
+                1 => EntitlementType::Purchase,
+                2 => EntitlementType::PremiumSubscription,
+                3 => EntitlementType::DeveloperGift,
+                4 => EntitlementType::TestModePurchase,
+                5 => EntitlementType::FreePurchase,
+                6 => EntitlementType::UserGift,
+                7 => EntitlementType::PremiumPurchase,
+                8 => EntitlementType::ApplicationSubscription,
+                other => EntitlementType::Other(other),
+            },
+            starts_at: entitlement.starts_at.clone(),
+            ends_at: entitlement.ends_at.clone(),
+            consumed: entitlement.consumed.unwrap_or(false),
+        }
+    }
+}
+
+impl From<Option<&discord_types::ResolvedData>> for Resolved {
+    fn from(resolved: Option<&discord_types::ResolvedData>) -> Self {
+        fn convert<'a, A: 'a, B: From<&'a A>>(
+            map: Option<&'a HashMap<Snowflake, A>>,
+        ) -> HashMap<Snowflake, B> {
+            map.iter()
+                .flat_map(|m| m.iter())
+                .map(|(id, v)| (id.clone(), v.into()))
+                .collect()
+        }
+
+        Resolved {
+            users: convert(resolved.and_then(|r| r.users.as_ref())),
+            members: convert(resolved.and_then(|r| r.members.as_ref())),
+            roles: convert(resolved.and_then(|r| r.roles.as_ref())),
+            channels: convert(resolved.and_then(|r| r.channels.as_ref())),
+            messages: convert(resolved.and_then(|r| r.messages.as_ref())),
+            attachments: convert(resolved.and_then(|r| r.attachments.as_ref())),
+        }
+    }
+}
+
+impl From<&discord_types::User> for User {
+    fn from(user: &discord_types::User) -> Self {
+        User {
+            id: user.id.clone(),
+            username: user.username.clone(),
+            global_name: user.global_name.clone(),
+            discriminator: user.discriminator.clone().unwrap_or("0".to_string()),
+            avatar: user.avatar.clone(),
+            banner: user.banner.clone(),
+            accent_color: user.accent_color,
+        }
+    }
+}
+
+impl From<&discord_types::GuildMember> for Member {
+    fn from(member: &discord_types::GuildMember) -> Self {
+        Member {
+            nick: member.nick.clone(),
+            avatar: member.avatar.clone(),
+            roles: member.roles.clone(),
+            joined_at: member.joined_at.clone(),
+            premium_since: member.premium_since.clone(),
+            permissions: member.permissions.as_ref().and_then(|p| p.parse().ok()),
+        }
+    }
+}
+
+impl From<&discord_types::Role> for Role {
+    fn from(role: &discord_types::Role) -> Self {
+        Role {
+            id: role.id.clone(),
+            name: role.name.clone(),
+            color: role.color,
+            hoist: role.hoist,
+            icon: role.icon.clone(),
+            unicode_emoji: role.unicode_emoji.clone(),
+            position: role.position,
+            permissions: role.permissions.parse().unwrap_or(0),
+            managed: role.managed,
+            mentionable: role.mentionable,
+            bot_id: role.tags.as_ref().and_then(|tags| tags.bot_id.clone()),
+            integration_id: role
+                .tags
+                .as_ref()
+                .and_then(|tags| tags.integration_id.clone()),
+        }
+    }
+}
+
+impl From<&discord_types::Channel> for Channel {
+    fn from(channel: &discord_types::Channel) -> Self {
+        Channel {
+            id: channel.id.clone(),
+            channel_type: match channel.r#type {
+                0 => ChannelType::GuildText,
+                1 => ChannelType::Dm,
+                2 => ChannelType::GuildVoice,
+                3 => ChannelType::GroupDm,
+                4 => ChannelType::GuildCategory,
+                5 => ChannelType::GuildAnnouncement,
+                10 => ChannelType::AnnouncementThread,
+                11 => ChannelType::PublicThread,
+                12 => ChannelType::PrivateThread,
+                13 => ChannelType::GuildStageVoice,
+                14 => ChannelType::GuildDirectory,
+                15 => ChannelType::GuildForum,
+                16 => ChannelType::GuildMedia,
+                other => ChannelType::Other(other),
+            },
+            name: channel.name.clone(),
+            parent_id: channel.parent_id.clone(),
+            permissions: channel.permissions.as_ref().and_then(|p| p.parse().ok()),
+        }
+    }
+}
+
+impl From<&discord_types::Attachment> for Attachment {
+    fn from(attachment: &discord_types::Attachment) -> Self {
+        Attachment {
+            id: attachment.id.clone(),
+            filename: attachment.filename.clone(),
+            description: attachment.description.clone(),
+            content_type: attachment.content_type.clone(),
+            size: attachment.size,
+            url: attachment.url.clone(),
+            proxy_url: attachment.proxy_url.clone(),
+            height: attachment.height,
+            width: attachment.width,
+        }
+    }
+}
+
+impl From<&discord_types::Message> for SourceMessage {
+    fn from(msg: &discord_types::Message) -> Self {
+        SourceMessage {
+            id: msg.id.clone(),
+            text: msg.content.clone(),
+            author: msg.author.as_ref().map(|author| author.into()),
+            timestamp: msg.timestamp.clone(),
+            edited_timestamp: msg.edited_timestamp.clone(),
+            flags: msg.flags.unwrap_or(0),
+            embeds: msg.embeds.iter().map(|embed| embed.into()).collect(),
+            buttons: flatten_components(&msg.components)
+                .into_iter()
+                .filter(|c| c.r#type == discord_types::ComponentType::Button)
+                .map(|c| Button {
+                    id: c.custom_id.clone().unwrap_or_default(),
+                    text: c.label.clone().unwrap_or_default(),
+                    style: match c.style {
+                        Some(2) => ButtonStyle::Secondary,
+                        Some(3) => ButtonStyle::Success,
+                        Some(4) => ButtonStyle::Danger,
+                        Some(5) => ButtonStyle::Link,
+                        Some(6) => ButtonStyle::Premium,
+                        _ => ButtonStyle::Primary,
+                    },
+                    url: c.url.clone(),
+                    sku_id: c.sku_id.clone(),
+                    emoji: c.emoji.as_ref().map(|emoji| emoji.into()),
+                    disabled: c.disabled.unwrap_or(false),
+                })
+                .collect(),
+            attachments: msg.attachments.iter().map(|a| a.into()).collect(),
+            poll: msg.poll.as_ref().map(|poll| poll.into()),
+        }
+    }
+}
+
+impl From<&discord_types::Embed> for Embed {
+    fn from(embed: &discord_types::Embed) -> Self {
+        Embed {
+            title: embed.title.clone(),
+            description: embed.description.clone(),
+            url: embed.url.clone(),
+            timestamp: embed.timestamp.clone(),
+            color: embed.color,
+            footer: embed.footer.as_ref().map(|footer| EmbedFooter {
+                text: footer.text.clone(),
+                icon_url: footer.icon_url.clone(),
+            }),
+            image: embed.image.as_ref().map(|image| image.url.clone()),
+            thumbnail: embed.thumbnail.as_ref().map(|thumb| thumb.url.clone()),
+            author: embed.author.as_ref().map(|author| EmbedAuthor {
+                name: author.name.clone(),
+                url: author.url.clone(),
+                icon_url: author.icon_url.clone(),
+            }),
+            fields: embed
+                .fields
+                .iter()
+                .map(|field| EmbedField {
+                    name: field.name.clone(),
+                    value: field.value.clone(),
+                    inline: field.inline,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Embed> for discord_types::Embed {
+    fn from(embed: Embed) -> Self {
+        discord_types::Embed {
+            title: embed.title,
+            description: embed.description,
+            url: embed.url,
+            timestamp: embed.timestamp,
+            color: embed.color,
+            footer: embed.footer.map(|footer| discord_types::EmbedFooter {
+                text: footer.text,
+                icon_url: footer.icon_url,
+            }),
+            image: embed.image.map(|url| discord_types::EmbedMedia { url }),
+            thumbnail: embed.thumbnail.map(|url| discord_types::EmbedMedia { url }),
+            author: embed.author.map(|author| discord_types::EmbedAuthor {
+                name: author.name,
+                url: author.url,
+                icon_url: author.icon_url,
+            }),
+            fields: embed
+                .fields
+                .into_iter()
+                .map(|field| discord_types::EmbedField {
+                    name: field.name,
+                    value: field.value,
+                    inline: field.inline,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<Message> for discord_types::InteractionResponse {
+    type Error = HandlerError;
+
+    /// Converts a message, failing if Discord would reject it.
+    fn try_from(message: Message) -> Result<Self, HandlerError> {
+        let is_layout = message.is_layout();
+        for component in &message.components {
+            if let TopLevelComponent::ActionRow(row) = component {
+                check_row(row)?;
+            }
+        }
+
+        let components = message
+            .components
+            .iter()
+            .map(|c| c.try_into())
+            .collect::<Result<Vec<discord_types::Component>, _>>()?;
+        let mut flags = message.flags;
+
+        if message.keep != Keep::default() && !message.edit {
+            return Err(HandlerError::new(
+                "Only an edit can keep parts of the original message!",
+            ));
+        }
+        if message.keep.text && !message.text.is_empty() {
+            return Err(HandlerError::new(
+                "An edit can't both keep and replace the original message's text!",
+            ));
+        }
+        if message.keep.embeds && !message.embeds.is_empty() {
+            return Err(HandlerError::new(
+                "An edit can't both keep and replace the original message's embeds!",
+            ));
+        }
+        if message.keep.components && !components.is_empty() {
+            return Err(HandlerError::new(
+                "An edit can't both keep and replace the original message's components!",
+            ));
+        }
+
+        if is_layout {
+            if !message.text.is_empty() || !message.embeds.is_empty() || message.poll.is_some() {
+                return Err(HandlerError::new(
+                    "A message with a layout can't have text, embeds or a poll!",
+                ));
+            }
+
+            if count_components(&components) > 40 {
+                return Err(HandlerError::new(
+                    "A message's layout can have at most 40 components!",
+                ));
+            }
+
+            flags |= message_flags::IS_COMPONENTS_V2;
+        } else if components.len() > 5 {
+            return Err(HandlerError::new(
+                "A message can have at most 5 rows of components!",
+            ));
+        }
+
+        check_custom_ids(&components)?;
+
+        if message.embeds.len() > 10 {
+            return Err(HandlerError::new("A message can have at most 10 embeds!"));
+        }
+
+        if message.files.len() > 10 {
+            return Err(HandlerError::new("A message can have at most 10 files!"));
+        }
+        if message.files.iter().any(|file| {
+            file.description
+                .as_ref()
+                .is_some_and(|d| d.chars().count() > 1024)
+        }) {
+            return Err(HandlerError::new(
+                "A file's description must be at most 1024 characters!",
+            ));
+        }
+
+        if let Some(poll) = &message.poll {
+            if message.edit {
+                return Err(HandlerError::new(
+                    "A poll can't be attached to an edited message!",
+                ));
+            }
+            if poll.answers.is_empty() || poll.answers.len() > 10 {
+                return Err(HandlerError::new(
+                    "A poll must have between 1 and 10 answers!",
+                ));
+            }
+            if poll.duration == 0 || poll.duration > 768 {
+                return Err(HandlerError::new(
+                    "A poll must last between 1 and 768 hours!",
+                ));
+            }
+        }
+
+        let attachments = message
+            .files
+            .iter()
+            .enumerate()
+            .map(|(id, file)| discord_types::AttachmentUpload {
+                id,
+                filename: file.filename.clone(),
+                description: file.description.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        Ok(discord_types::InteractionResponse {
+            r#type: if message.edit {
+                discord_types::InteractionCallbackType::UpdateMessage
+            } else {
+                discord_types::InteractionCallbackType::ChannelMessageWithSource
+            },
+
+            data: Some(discord_types::InteractionCallbackData {
+                // Parts that are left out of an edit are kept as they are.
+                content: (!is_layout && !message.keep.text).then_some(message.text),
+                tts: message.tts.then_some(true),
+                embeds: (!is_layout && !message.keep.embeds)
+                    .then(|| message.embeds.into_iter().map(|e| e.into()).collect()),
+                components: (!message.keep.components).then_some(components),
+                flags: Some(flags),
+                custom_id: None,
+                title: None,
+                allowed_mentions: message
+                    .allowed_mentions
+                    .map(|am| am.try_into())
+                    .transpose()?,
+                attachments: (!attachments.is_empty()).then_some(attachments),
+                poll: message.poll.map(|poll| poll.into()),
+                choices: None,
+            }),
+            files: message
+                .files
+                .into_iter()
+                .map(|file| discord_types::FileData {
+                    filename: file.filename,
+                    data: file.data,
+                })
+                .collect(),
+            raw: None,
+            followup: None,
+        })
+    }
+}
+
+impl TryFrom<Followup> for discord_types::InteractionResponse {
+    type Error = HandlerError;
+
+    /// Converts a followup, failing if Discord would reject it. Only the converted data and files
+    /// are sent, to the interaction's webhook.
+    fn try_from(followup: Followup) -> Result<Self, HandlerError> {
+        if followup.message.edit {
+            return Err(HandlerError::new("A followup can't edit a message!"));
+        }
+
+        followup.message.try_into()
+    }
+}
+
+/// Checks that the given components can be placed in a single row: a row must hold either 1 to 5
+/// buttons, or a single select menu.
+fn check_row(row: &[RowComponent]) -> Result<(), HandlerError> {
+    let buttons = row
+        .iter()
+        .filter(|c| matches!(c, RowComponent::Button(_)))
+        .count();
+
+    let err = match (row.len(), buttons) {
+        (0, _) => "A row of components can't be empty!",
+        (1..=5, n) if n == row.len() => return Ok(()),
+        (1, 0) => return Ok(()),
+        (_, 0) => "A row can hold only a single select menu!",
+        (_, n) if n == row.len() => "A row can hold at most 5 buttons!",
+        _ => "A row can't hold both buttons and a select menu!",
+    };
+    Err(HandlerError::new(err))
+}
+
+/// Checks that the custom ids in the given component tree are at most 100 characters long, and
+/// that no two components share one. Discord rejects such messages, and even if it didn't, the
+/// resulting interactions couldn't be told apart.
+fn check_custom_ids(components: &[discord_types::Component]) -> Result<(), HandlerError> {
+    let mut seen = HashSet::new();
+
+    for c in flatten_components(components) {
+        if let Some(id) = &c.custom_id {
+            if id.chars().count() > MAX_CUSTOM_ID_LENGTH {
+                return Err(HandlerError::new(&format!(
+                    "Component id `{}` must be at most {} characters!",
+                    id, MAX_CUSTOM_ID_LENGTH
+                )));
+            }
+            if !seen.insert(id) {
+                return Err(HandlerError::new(&format!(
+                    "Duplicate component id `{}` in message!",
+                    id
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the components in the given component tree, including those nested in rows, sections
+/// (and their accessories) and containers, in the order they're displayed.
+fn flatten_components(components: &[discord_types::Component]) -> Vec<&discord_types::Component> {
+    let mut all = Vec::new();
+    let mut stack: Vec<&discord_types::Component> = components.iter().rev().collect();
+
+    while let Some(c) = stack.pop() {
+        all.push(c);
+        // A section's accessory is displayed after its text.
+        stack.extend(c.accessory.as_deref());
+        stack.extend(c.components.iter().flatten().rev());
+    }
+
+    all
+}
+
+/// Counts the components in the given component tree, including nested components.
+fn count_components(components: &[discord_types::Component]) -> usize {
+    flatten_components(components).len()
+}
+
+impl TryFrom<Modal> for discord_types::InteractionResponse {
+    type Error = HandlerError;
+
+    /// Converts a modal, failing if Discord would reject it.
+    fn try_from(modal: Modal) -> Result<Self, HandlerError> {
+        if modal.fields.len() > 5 {
+            return Err(HandlerError::new("Modal can have at most 5 fields!"));
+        }
+
+        for field in &modal.fields {
+            let field = match field {
+                ModalComponent::Text(field) => field,
+                ModalComponent::Select { select, .. } => {
+                    if select.disabled {
+                        return Err(HandlerError::new(&format!(
+                            "Select menu `{}` can't be disabled in a modal!",
+                            select.id
+                        )));
+                    }
+                    continue;
+                }
+            };
+
+            if field.min_length.is_some_and(|min| min > 4000) {
+                return Err(HandlerError::new(
+                    "Modal field's minimum length must be at most 4000!",
+                ));
+            }
+            if field
+                .max_length
+                .is_some_and(|max| !(1..=4000).contains(&max))
+            {
+                return Err(HandlerError::new(
+                    "Modal field's maximum length must be between 1 and 4000!",
+                ));
+            }
+            if let (Some(min), Some(max)) = (field.min_length, field.max_length) {
+                if min > max {
+                    return Err(HandlerError::new(
+                        "Modal field's minimum length can't exceed its maximum length!",
+                    ));
+                }
+            }
+            if field
+                .placeholder
+                .as_ref()
+                .is_some_and(|p| p.chars().count() > 100)
+            {
+                return Err(HandlerError::new(
+                    "Modal field's placeholder must be at most 100 characters!",
+                ));
+            }
+            if field
+                .value
+                .as_ref()
+                .is_some_and(|v| v.chars().count() > 4000)
+            {
+                return Err(HandlerError::new(
+                    "Modal field's value must be at most 4000 characters!",
+                ));
+            }
+        }
+
+        let fields = modal
+            .fields
+            .iter()
+            .map(|field| match field {
+                // Select menus in modals must be wrapped in a label, rather than a row.
+                ModalComponent::Select { label, select } => Ok(discord_types::Component {
+                    r#type: discord_types::ComponentType::Label,
+                    label: Some(label.clone()),
+                    component: Some(Box::new(discord_types::Component {
+                        required: (select.min_values == 0).then_some(false),
+                        ..select.try_into()?
+                    })),
+                    ..Default::default()
+                }),
+                ModalComponent::Text(field) => Ok(discord_types::Component {
+                    r#type: discord_types::ComponentType::ActionRow,
+                    components: Some(vec![discord_types::Component {
+                        r#type: discord_types::ComponentType::TextInput,
+                        label: Some(field.label.clone()),
+                        style: Some(match field.style {
+                            TextInputStyle::Short => discord_types::TextInputStyle::Short,
+                            TextInputStyle::Paragraph => discord_types::TextInputStyle::Paragraph,
+                        } as u8),
+                        custom_id: Some(field.id.clone()),
+                        placeholder: field.placeholder.clone(),
+                        min_length: field.min_length,
+                        max_length: field.max_length,
+                        required: (!field.required).then_some(false),
+                        value: field.value.clone(),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }),
+            })
+            .collect::<Result<Vec<_>, HandlerError>>()?;
+
+        let data = discord_types::InteractionCallbackData {
+            content: None,
+            tts: None,
+            embeds: None,
+            flags: None,
+            components: Some(fields),
+            custom_id: Some(modal.id),
+            title: Some(modal.title),
+            allowed_mentions: None,
+            attachments: None,
+            poll: None,
+            choices: None,
+        };
+
+        Ok(discord_types::InteractionResponse {
+            r#type: discord_types::InteractionCallbackType::Modal,
+            data: Some(data),
+            files: Vec::new(),
+            raw: None,
+            followup: None,
+        })
+    }
+}
+
+impl TryFrom<AllowedMentions> for discord_types::AllowedMentions {
+    type Error = HandlerError;
+
+    /// Converts allowed mentions, failing if Discord would reject them.
+    fn try_from(am: AllowedMentions) -> Result<Self, HandlerError> {
+        // Discord rejects allowed mentions that list specific ids of a kind it already parses.
+        if am.users && !am.user_ids.is_empty() {
+            return Err(HandlerError::new(
+                "Allowed mentions can't list specific users when all users are allowed!",
+            ));
+        }
+        if am.roles && !am.role_ids.is_empty() {
+            return Err(HandlerError::new(
+                "Allowed mentions can't list specific roles when all roles are allowed!",
+            ));
+        }
+        if am.user_ids.len() > 100 || am.role_ids.len() > 100 {
+            return Err(HandlerError::new(
+                "Allowed mentions can list at most 100 users and 100 roles!",
+            ));
+        }
+
+        let kinds = [
+            (am.users, discord_types::AllowedMentionType::Users),
+            (am.roles, discord_types::AllowedMentionType::Roles),
+            (am.everyone, discord_types::AllowedMentionType::Everyone),
+        ];
+
+        Ok(discord_types::AllowedMentions {
+            parse: kinds
+                .into_iter()
+                .filter_map(|(allowed, kind)| allowed.then_some(kind))
+                .collect(),
+            users: am.user_ids,
+            roles: am.role_ids,
+            replied_user: am.replied_user.then_some(true),
+        })
+    }
+}
+
+impl From<&Button> for discord_types::Component {
+    fn from(button: &Button) -> Self {
+        discord_types::Component {
+            r#type: discord_types::ComponentType::Button,
+            // Premium buttons must not have a label.
+            label: (button.style != ButtonStyle::Premium).then(|| button.text.clone()),
+            style: Some(match button.style {
+                ButtonStyle::Primary => discord_types::ButtonStyle::Primary,
+                ButtonStyle::Secondary => discord_types::ButtonStyle::Secondary,
+                ButtonStyle::Success => discord_types::ButtonStyle::Success,
+                ButtonStyle::Danger => discord_types::ButtonStyle::Danger,
+                ButtonStyle::Link => discord_types::ButtonStyle::Link,
+                ButtonStyle::Premium => discord_types::ButtonStyle::Premium,
+            } as u8),
+            // Link and premium buttons must not have a custom id.
+            custom_id: (!matches!(button.style, ButtonStyle::Link | ButtonStyle::Premium))
+                .then(|| button.id.clone()),
+            url: button.url.clone(),
+            sku_id: button.sku_id.clone(),
+            emoji: button.emoji.clone().map(|emoji| emoji.into()),
+            disabled: button.disabled.then_some(true),
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<&SelectMenu> for discord_types::Component {
+    type Error = HandlerError;
+
+    /// Converts a select menu, failing if Discord would reject it.
+    fn try_from(select: &SelectMenu) -> Result<Self, HandlerError> {
+        let max_options = match select.menu_type {
+            SelectMenuType::String => {
+                if select.options.is_empty() || select.options.len() > 25 {
+                    return Err(HandlerError::new(&format!(
+                        "Select menu `{}` must have between 1 and 25 options!",
+                        select.id
+                    )));
+                }
+                select.options.len()
+            }
+
+            _ => {
+                if !select.options.is_empty() {
+                    return Err(HandlerError::new(&format!(
+                        "Select menu `{}` is populated by Discord, and can't have options!",
+                        select.id
+                    )));
+                }
+                25
+            }
+        };
+
+        let mut seen = HashSet::new();
+        for option in &select.options {
+            if !seen.insert(&option.value) {
+                return Err(HandlerError::new(&format!(
+                    "Duplicate option value `{}` in select menu `{}`!",
+                    option.value, select.id
+                )));
+            }
+        }
+
+        if select.min_values > select.max_values || select.max_values as usize > max_options {
+            return Err(HandlerError::new(&format!(
+                "Select menu `{}` allows choosing between {} and {} of at most {} options!",
+                select.id, select.min_values, select.max_values, max_options
+            )));
+        }
+
+        Ok(discord_types::Component {
+            r#type: match select.menu_type {
+                SelectMenuType::String => discord_types::ComponentType::StringSelect,
+                SelectMenuType::User => discord_types::ComponentType::UserSelect,
+                SelectMenuType::Role => discord_types::ComponentType::RoleSelect,
+                SelectMenuType::Mentionable => discord_types::ComponentType::MentionableSelect,
+                SelectMenuType::Channel => discord_types::ComponentType::ChannelSelect,
+            },
+            custom_id: Some(select.id.clone()),
+            options: (select.menu_type == SelectMenuType::String).then(|| {
+                select
+                    .options
+                    .iter()
+                    .map(|option| discord_types::SelectOption {
+                        label: option.label.clone(),
+                        value: option.value.clone(),
+                        description: option.description.clone(),
+                        default: option.default,
+                        emoji: option.emoji.clone().map(|emoji| emoji.into()),
+                    })
+                    .collect()
+            }),
+            placeholder: select.placeholder.clone(),
+            min_values: Some(select.min_values),
+            max_values: Some(select.max_values),
+            disabled: select.disabled.then_some(true),
+            ..Default::default()
+        })
+    }
+}
+
+impl From<&discord_types::Emoji> for Emoji {
+    fn from(emoji: &discord_types::Emoji) -> Self {
+        let name = emoji.name.clone().unwrap_or_default();
+        match &emoji.id {
+            Some(id) => Emoji::Custom {
+                id: id.clone(),
+                name,
+                animated: emoji.animated,
+            },
+            None => Emoji::Unicode(name),
+        }
+    }
+}
+
+impl From<Emoji> for discord_types::Emoji {
+    fn from(emoji: Emoji) -> Self {
+        match emoji {
+            Emoji::Unicode(name) => discord_types::Emoji {
+                id: None,
+                name: Some(name),
+                animated: false,
+            },
+            Emoji::Custom { id, name, animated } => discord_types::Emoji {
+                id: Some(id),
+                name: Some(name),
+                animated,
+            },
+        }
+    }
+}
+
+impl TryFrom<&RowComponent> for discord_types::Component {
+    type Error = HandlerError;
+
+    fn try_from(component: &RowComponent) -> Result<Self, HandlerError> {
+        match component {
+            RowComponent::Button(button) => Ok(button.into()),
+            RowComponent::Select(select) => select.try_into(),
+        }
+    }
+}
+
+impl From<Button> for RowComponent {
+    fn from(button: Button) -> Self {
+        RowComponent::Button(button)
+    }
+}
+
+impl From<SelectMenu> for RowComponent {
+    fn from(select: SelectMenu) -> Self {
+        RowComponent::Select(select)
+    }
+}
+
+impl From<&discord_types::Poll> for Poll {
+    fn from(poll: &discord_types::Poll) -> Self {
+        Poll {
+            question: poll.question.text.clone().unwrap_or_default(),
+            answers: poll
+                .answers
                 .iter()
-                .map(|row| {
-                    let inner = &row.components.as_ref().unwrap()[0];
-                    (
-                        inner.custom_id.as_ref().unwrap().clone(),
-                        inner.value.as_ref().unwrap().clone(),
-                    )
+                .map(|answer| PollAnswer {
+                    id: answer.answer_id.unwrap_or(0),
+                    text: answer.poll_media.text.clone().unwrap_or_default(),
+                    emoji: answer.poll_media.emoji.as_ref().map(|emoji| emoji.into()),
                 })
                 .collect(),
+            duration: poll.duration.unwrap_or(0),
+            allow_multiselect: poll.allow_multiselect,
+            expiry: poll.expiry.clone(),
+            results: poll.results.as_ref().map(|results| PollResults {
+                finalized: results.is_finalized,
+                counts: results
+                    .answer_counts
+                    .iter()
+                    .map(|count| (count.id, count.count))
+                    .collect(),
+                voted: results
+                    .answer_counts
+                    .iter()
+                    .filter(|count| count.me_voted)
+                    .map(|count| count.id)
+                    .collect(),
+            }),
+        }
+    }
+}
 
-            source: req.message.as_ref().unwrap().into(),
+impl From<Poll> for discord_types::Poll {
+    fn from(poll: Poll) -> Self {
+        discord_types::Poll {
+            question: discord_types::PollMedia {
+                text: Some(poll.question),
+                emoji: None,
+            },
+            answers: poll
+                .answers
+                .into_iter()
+                .map(|answer| discord_types::PollAnswer {
+                    answer_id: None,
+                    poll_media: discord_types::PollMedia {
+                        text: Some(answer.text),
+                        emoji: answer.emoji.map(|emoji| emoji.into()),
+                    },
+                })
+                .collect(),
+            duration: Some(poll.duration),
+            allow_multiselect: poll.allow_multiselect,
+            expiry: None,
+            results: None,
         }
     }
 }
 
-impl From<&discord_types::Message> for SourceMessage {
-    fn from(msg: &discord_types::Message) -> Self {
-        SourceMessage {
-            text: msg.content.clone(),
+impl TryFrom<Response> for discord_types::InteractionResponse {
+    type Error = HandlerError;
+
+    /// Converts a response, failing if Discord would reject it.
+    fn try_from(res: Response) -> Result<Self, HandlerError> {
+        let res = match res {
+            Response::Message(message) => return message.try_into(),
+            Response::Modal(modal) => return modal.try_into(),
+            Response::Raw(raw) => discord_types::InteractionResponse {
+                r#type: serde_json::from_value(raw["type"].clone()).map_err(|_| {
+                    HandlerError::new("Raw response must have a known interaction callback type!")
+                })?,
+                data: None,
+                files: Vec::new(),
+                raw: Some(raw),
+                followup: None,
+            },
+            Response::Deferred { ephemeral } => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::DeferredChannelMessageWithSource,
+                data: Some(discord_types::InteractionCallbackData {
+                    flags: Some(if ephemeral {
+                        message_flags::EPHEMERAL
+                    } else {
+                        0
+                    }),
+                    ..Default::default()
+                }),
+                files: Vec::new(),
+                raw: None,
+                followup: None,
+            },
+            #[cfg(feature = "download")]
+            Response::UpdateWithFollowup { update, followup } => {
+                let mut res = discord_types::InteractionResponse::try_from(update.edit())?;
+                res.followup = Some(Box::new(followup.try_into()?));
+                res
+            }
+            Response::DeferredUpdate => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::DeferredUpdateMessage,
+                data: None,
+                files: Vec::new(),
+                raw: None,
+                followup: None,
+            },
+            Response::PremiumRequired => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::PremiumRequired,
+                data: None,
+                files: Vec::new(),
+                raw: None,
+                followup: None,
+            },
+            Response::LaunchActivity => discord_types::InteractionResponse {
+                r#type: discord_types::InteractionCallbackType::LaunchActivity,
+                data: None,
+                files: Vec::new(),
+                raw: None,
+                followup: None,
+            },
+            Response::Autocomplete(choices) => {
+                if choices.len() > 25 {
+                    return Err(HandlerError::new(
+                        "Autocomplete can suggest at most 25 choices!",
+                    ));
+                }
+
+                discord_types::InteractionResponse {
+                    r#type:
+                        discord_types::InteractionCallbackType::ApplicationCommandAutocompleteResult,
+                    data: Some(discord_types::InteractionCallbackData {
+                        choices: Some(
+                            choices
+                                .into_iter()
+                                .map(|c| c.try_into())
+                                .collect::<Result<_, _>>()?,
+                        ),
+                        ..Default::default()
+                    }),
+                    files: Vec::new(),
+                    raw: None,
+                    followup: None,
+                }
+            }
+        };
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<Choice> for discord_types::CommandChoice {
+    type Error = HandlerError;
+
+    /// Converts a choice, failing if Discord would reject it.
+    fn try_from(choice: Choice) -> Result<Self, HandlerError> {
+        if choice.name.is_empty() || choice.name.chars().count() > 100 {
+            return Err(HandlerError::new(&format!(
+                "Choice name `{}` must be between 1 and 100 characters!",
+                choice.name
+            )));
         }
+
+        Ok(discord_types::CommandChoice {
+            name: choice.name,
+            name_localizations: choice.name_localizations,
+            value: match choice.value {
+                ChoiceValue::String(s) => s.into(),
+                ChoiceValue::Integer(n) => n.into(),
+                ChoiceValue::Number(n) => n.into(),
+            },
+        })
     }
 }
 
-impl Into<discord_types::InteractionResponse> for Message {
-    fn into(self) -> discord_types::InteractionResponse {
-        let rows = self
-            .buttons
-            .chunks(5)
-            .map(|chunk| discord_types::Component {
-                r#type: discord_types::ComponentType::ActionRow,
-                label: None,
-                style: None,
-                custom_id: None,
-                value: None,
-                components: Some(chunk.iter().map(|b| b.into()).collect()),
-            })
-            .collect();
+impl TryFrom<&LayoutComponent> for discord_types::Component {
+    type Error = HandlerError;
 
-        discord_types::InteractionResponse {
-            r#type: if self.edit {
-                discord_types::InteractionCallbackType::UpdateMessage
-            } else {
-                discord_types::InteractionCallbackType::ChannelMessageWithSource
+    /// Converts a layout component, failing if Discord would reject it.
+    fn try_from(component: &LayoutComponent) -> Result<Self, HandlerError> {
+        use discord_types::ComponentType as T;
+
+        let component = match component {
+            LayoutComponent::Text(content) => discord_types::Component {
+                r#type: T::TextDisplay,
+                content: Some(content.clone()),
+                ..Default::default()
             },
 
-            data: Some(discord_types::InteractionCallbackData {
-                content: Some(self.text),
-                components: Some(rows),
-                flags: Some(if self.ephemeral { 64 } else { 0 }),
-                custom_id: None,
-                title: None,
-            }),
+            LayoutComponent::Section(section) => {
+                if section.texts.is_empty() || section.texts.len() > 3 {
+                    return Err(HandlerError::new(
+                        "A section must have between 1 and 3 pieces of text!",
+                    ));
+                }
+
+                discord_types::Component {
+                    r#type: T::Section,
+                    components: Some(
+                        section
+                            .texts
+                            .iter()
+                            .map(|text| (&LayoutComponent::Text(text.clone())).try_into())
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    accessory: Some(Box::new(match &section.accessory {
+                        SectionAccessory::Thumbnail(item) => discord_types::Component {
+                            r#type: T::Thumbnail,
+                            media: Some(discord_types::UnfurledMedia {
+                                url: item.url.clone(),
+                            }),
+                            description: item.description.clone(),
+                            spoiler: item.spoiler.then_some(true),
+                            ..Default::default()
+                        },
+                        SectionAccessory::Button(button) => button.into(),
+                    })),
+                    ..Default::default()
+                }
+            }
+
+            LayoutComponent::MediaGallery(items) => {
+                if items.is_empty() || items.len() > 10 {
+                    return Err(HandlerError::new(
+                        "A media gallery must have between 1 and 10 items!",
+                    ));
+                }
+
+                discord_types::Component {
+                    r#type: T::MediaGallery,
+                    items: Some(
+                        items
+                            .iter()
+                            .map(|item| discord_types::MediaGalleryItem {
+                                media: discord_types::UnfurledMedia {
+                                    url: item.url.clone(),
+                                },
+                                description: item.description.clone(),
+                                spoiler: item.spoiler,
+                            })
+                            .collect(),
+                    ),
+                    ..Default::default()
+                }
+            }
+
+            LayoutComponent::Separator { divider, large } => discord_types::Component {
+                r#type: T::Separator,
+                divider: Some(*divider),
+                spacing: Some(if *large { 2 } else { 1 }),
+                ..Default::default()
+            },
+
+            LayoutComponent::File { filename, spoiler } => discord_types::Component {
+                r#type: T::File,
+                file: Some(discord_types::UnfurledMedia {
+                    url: format!("attachment://{}", filename),
+                }),
+                spoiler: spoiler.then_some(true),
+                ..Default::default()
+            },
+
+            LayoutComponent::Container(container) => {
+                if container
+                    .components
+                    .iter()
+                    .any(|c| matches!(c, LayoutComponent::Container(_)))
+                {
+                    return Err(HandlerError::new("Containers can't be nested!"));
+                }
+
+                discord_types::Component {
+                    r#type: T::Container,
+                    components: Some(
+                        container
+                            .components
+                            .iter()
+                            .map(|c| c.try_into())
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    accent_color: container.accent_color,
+                    spoiler: container.spoiler.then_some(true),
+                    ..Default::default()
+                }
+            }
+
+            LayoutComponent::Row(row) => action_row(row)?,
+        };
+
+        Ok(component)
+    }
+}
+
+impl TryFrom<&TopLevelComponent> for discord_types::Component {
+    type Error = HandlerError;
+
+    fn try_from(component: &TopLevelComponent) -> Result<Self, HandlerError> {
+        match component {
+            TopLevelComponent::ActionRow(row) => action_row(row),
+            TopLevelComponent::Layout(component) => component.try_into(),
         }
     }
 }
 
-impl Into<discord_types::InteractionResponse> for Modal {
-    fn into(self) -> discord_types::InteractionResponse {
-        let fields = self
-            .fields
+/// Converts a row of components, failing if it isn't valid.
+fn action_row(row: &[RowComponent]) -> Result<discord_types::Component, HandlerError> {
+    check_row(row)?;
+
+    Ok(discord_types::Component {
+        r#type: discord_types::ComponentType::ActionRow,
+        components: Some(row.iter().map(|c| c.try_into()).collect::<Result<_, _>>()?),
+        ..Default::default()
+    })
+}
+
+impl From<Section> for LayoutComponent {
+    fn from(section: Section) -> Self {
+        LayoutComponent::Section(section)
+    }
+}
+
+impl From<Container> for LayoutComponent {
+    fn from(container: Container) -> Self {
+        LayoutComponent::Container(container)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discord_types::InteractionRequest;
+    use serde_json::json;
+
+    fn component_request(guild_id: Option<&str>) -> InteractionRequest {
+        let user = json!({ "id": "53908232506183680", "username": "mason" });
+        let mut req = json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 3,
+            "channel_id": "645027906669510667",
+            "data": { "custom_id": "edit_config", "component_type": 2 },
+            "message": { "id": "111111111111111111", "content": "Server settings" },
+        });
+
+        match guild_id {
+            Some(guild_id) => {
+                req["guild_id"] = json!(guild_id);
+                req["member"] = json!({ "user": user, "roles": [] });
+            }
+            None => req["user"] = user,
+        }
+
+        InteractionRequest::from_json(&req.to_string()).unwrap()
+    }
+
+    #[test]
+    fn component_guild_id() {
+        let mc = MessageComponent::from(&component_request(Some("290926798626357999")));
+        assert_eq!(mc.guild_id(), Some(&Snowflake::from("290926798626357999")));
+        assert_eq!(mc.user_id, Snowflake::from("53908232506183680"));
+
+        let mc = MessageComponent::from(&component_request(None));
+        assert_eq!(mc.guild_id(), None);
+    }
+
+    #[test]
+    fn source_message_buttons_in_layouts() {
+        let msg: discord_types::Message = serde_json::from_value(json!({
+            "id": "111111111111111111",
+            "content": "",
+            "flags": message_flags::IS_COMPONENTS_V2,
+            "components": [
+                {
+                    "type": 17,
+                    "components": [
+                        {
+                            "type": 9,
+                            "components": [{ "type": 10, "content": "Settings" }],
+                            "accessory": {
+                                "type": 2,
+                                "style": 1,
+                                "custom_id": "edit",
+                                "label": "Edit",
+                            },
+                        },
+                        {
+                            "type": 1,
+                            "components": [
+                                { "type": 2, "style": 4, "custom_id": "reset", "label": "Reset" },
+                            ],
+                        },
+                    ],
+                },
+                {
+                    "type": 1,
+                    "components": [
+                        { "type": 2, "style": 2, "custom_id": "close", "label": "Close" },
+                    ],
+                },
+            ],
+        }))
+        .unwrap();
+
+        let source = SourceMessage::from(&msg);
+        let ids: Vec<&str> = source.buttons.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, vec!["edit", "reset", "close"]);
+        assert_eq!(source.buttons[1].style, ButtonStyle::Danger);
+    }
+
+    #[test]
+    fn select_option_fields() {
+        let menu = SelectMenu::new("role")
+            .option(
+                SelectOption::new("Red", "red")
+                    .description("The red team")
+                    .emoji(Emoji::Unicode("🟥".to_string()))
+                    .default(true),
+            )
+            .option(SelectOption::new("Blue", "blue").default(false));
+        let component: discord_types::Component = (&menu).try_into().unwrap();
+
+        assert_eq!(
+            serde_json::to_value(component.options.unwrap()).unwrap(),
+            json!([
+                {
+                    "label": "Red",
+                    "value": "red",
+                    "description": "The red team",
+                    "default": true,
+                    "emoji": { "name": "🟥", "animated": false },
+                },
+                { "label": "Blue", "value": "blue" },
+            ])
+        );
+    }
+
+    #[test]
+    fn select_menu_errors() {
+        let menu = SelectMenu::new("color")
+            .option(SelectOption::new("Red", "red"))
+            .option(SelectOption::new("Crimson", "red"));
+        let err = discord_types::Component::try_from(&menu).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Duplicate option value `red` in select menu `color`!"
+        );
+
+        let msg = Message::new().select(SelectMenu::new("empty"));
+        let err = discord_types::InteractionResponse::try_from(msg).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Select menu `empty` must have between 1 and 25 options!"
+        );
+    }
+
+    #[test]
+    fn error_and_success_messages() {
+        let msg = Message::error("That didn't work.");
+        assert_eq!(msg.flags, message_flags::EPHEMERAL);
+        assert_eq!(msg.embeds[0].title.as_deref(), Some("⚠️ Error"));
+        assert_eq!(
+            msg.embeds[0].description.as_deref(),
+            Some("That didn't work.")
+        );
+        assert_eq!(msg.embeds[0].color, Some(ERROR_COLOR));
+
+        let msg = Message::success("Saved.");
+        assert_eq!(msg.flags, message_flags::EPHEMERAL);
+        assert_eq!(msg.embeds[0].title.as_deref(), Some("✅ Success"));
+        assert_eq!(msg.embeds[0].color, Some(SUCCESS_COLOR));
+
+        let embed = Embed::error("Nope.").title("Not allowed").color(0xFFA500);
+        assert_eq!(embed.title.as_deref(), Some("Not allowed"));
+        assert_eq!(embed.color, Some(0xFFA500));
+    }
+
+    #[test]
+    fn layout_rows() {
+        let layout = Layout::new()
+            .row(|r| {
+                r.button(Button::new("yes", "Yes"))
+                    .button(Button::new("no", "No"))
+            })
+            .row(|r| r.select(SelectMenu::new("color").option(SelectOption::new("Red", "red"))));
+        let res: discord_types::InteractionResponse =
+            Message::new().rows(layout).try_into().unwrap();
+        let rows = res.data.unwrap().components.unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].components.as_ref().unwrap().len(), 2);
+        assert_eq!(
+            rows[1].components.as_ref().unwrap()[0].r#type,
+            discord_types::ComponentType::StringSelect
+        );
+    }
+
+    #[test]
+    fn layout_row_is_checked() {
+        let layout = Layout::new().row(|r| {
+            r.button(Button::new("yes", "Yes"))
+                .select(SelectMenu::new("color").option(SelectOption::new("Red", "red")))
+        });
+        let err =
+            discord_types::InteractionResponse::try_from(Message::new().rows(layout)).unwrap_err();
+        assert_eq!(
+            err.message,
+            "A row can't hold both buttons and a select menu!"
+        );
+    }
+
+    fn response_json(msg: Message) -> serde_json::Value {
+        let res: discord_types::InteractionResponse = msg.try_into().unwrap();
+        serde_json::to_value(res).unwrap()
+    }
+
+    fn first_button(message: &Message) -> &Button {
+        match message.components.first() {
+            Some(TopLevelComponent::ActionRow(row)) => match row.first() {
+                Some(RowComponent::Button(button)) => button,
+                _ => panic!("Expected a button"),
+            },
+            _ => panic!("Expected a row"),
+        }
+    }
+
+    #[test]
+    fn limits_are_reported_as_errors() {
+        let err = |res: Response| {
+            discord_types::InteractionResponse::try_from(res)
+                .unwrap_err()
+                .message
+        };
+
+        let mut mentions = AllowedMentions::none().users();
+        mentions.user_ids.push("53908232506183680".into());
+        assert_eq!(
+            err(Response::Message(
+                Message::new().text("Hi").allowed_mentions(mentions)
+            )),
+            "Allowed mentions can't list specific users when all users are allowed!"
+        );
+
+        assert_eq!(
+            err(Response::Autocomplete(vec![Choice::string("", "empty")])),
+            "Choice name `` must be between 1 and 100 characters!"
+        );
+
+        let nested = Container::new().component(Container::new().into());
+        assert_eq!(
+            err(Response::Message(Message::new().component(nested.into()))),
+            "Containers can't be nested!"
+        );
+
+        assert_eq!(
+            err(Response::Message(
+                Message::new().component(LayoutComponent::MediaGallery(Vec::new()))
+            )),
+            "A media gallery must have between 1 and 10 items!"
+        );
+
+        assert_eq!(
+            err(Response::Message(
+                Message::new().component(LayoutComponent::Row(Vec::new()))
+            )),
+            "A row of components can't be empty!"
+        );
+    }
+
+    #[test]
+    fn components_keep_their_order() {
+        let json = response_json(
+            Message::new()
+                .button("a", "A")
+                .button("b", "B")
+                .select(SelectMenu::new("color").option(SelectOption::new("Red", "red")))
+                .button("c", "C"),
+        );
+        let rows = json["data"]["components"].as_array().unwrap();
+        let ids: Vec<Vec<&str>> = rows
             .iter()
-            .map(|field| discord_types::Component {
-                r#type: discord_types::ComponentType::ActionRow,
-                label: None,
-                style: None,
-                custom_id: None,
-                value: None,
-                components: Some(vec![discord_types::Component {
-                    r#type: discord_types::ComponentType::TextInput,
-                    label: Some(field.label.clone()),
-                    style: Some(1),
-                    custom_id: Some(field.id.clone()),
-                    value: None,
-                    components: None,
-                }]),
+            .map(|row| {
+                row["components"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|c| c["custom_id"].as_str().unwrap())
+                    .collect()
             })
             .collect();
+        assert_eq!(ids, vec![vec!["a", "b"], vec!["color"], vec!["c"]]);
+        assert_eq!(json["data"]["flags"], 0);
 
-        let data = discord_types::InteractionCallbackData {
-            content: None,
-            flags: None,
-            components: Some(fields),
-            custom_id: Some(self.id),
-            title: Some(self.title),
+        let json = response_json(
+            Message::new()
+                .button("a", "A")
+                .component(LayoutComponent::Text("Hello".to_string()))
+                .row(vec![Button::new("b", "B").into()]),
+        );
+        let types: Vec<u64> = json["data"]["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["type"].as_u64().unwrap())
+            .collect();
+        assert_eq!(types, vec![1, 10, 1]);
+        assert_eq!(json["data"]["flags"], message_flags::IS_COMPONENTS_V2);
+    }
+
+    #[test]
+    fn classic_components() {
+        let json = response_json(
+            Message::new()
+                .text("Pick one")
+                .button("a", "A")
+                .button("b", "B")
+                .select(SelectMenu::new("menu").option(SelectOption::new("Red", "red")))
+                .row(vec![Button::new("c", "C").into()]),
+        );
+
+        assert_eq!(json["data"]["flags"], 0);
+        let rows = json["data"]["components"].as_array().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row["type"] == 1));
+        assert_eq!(rows[0]["components"][1]["custom_id"], "b");
+        assert_eq!(rows[1]["components"][0]["type"], 3);
+        assert_eq!(rows[2]["components"][0]["custom_id"], "c");
+    }
+
+    #[test]
+    fn layout_components() {
+        let json = response_json(
+            Message::new()
+                .component(LayoutComponent::text("# Settings"))
+                .component(
+                    Container::new()
+                        .component(LayoutComponent::text("Inside"))
+                        .into(),
+                )
+                .button("save", "Save"),
+        );
+
+        assert_eq!(json["data"]["flags"], message_flags::IS_COMPONENTS_V2);
+        assert!(json["data"].get("content").is_none());
+        let components = json["data"]["components"].as_array().unwrap();
+        let types: Vec<_> = components.iter().map(|c| c["type"].clone()).collect();
+        assert_eq!(types, vec![json!(10), json!(17), json!(1)]);
+        assert_eq!(components[1]["components"][0]["content"], "Inside");
+    }
+
+    #[test]
+    fn components_v2_flag_without_layout() {
+        let json = response_json(
+            Message::new()
+                .flags(message_flags::IS_COMPONENTS_V2)
+                .button("save", "Save"),
+        );
+
+        assert_eq!(json["data"]["flags"], message_flags::IS_COMPONENTS_V2);
+        assert!(json["data"].get("content").is_none());
+        assert_eq!(json["data"]["components"][0]["type"], 1);
+    }
+
+    fn autocomplete_request(options: serde_json::Value) -> InteractionRequest {
+        let req = json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 4,
+            "user": { "id": "53908232506183680", "username": "mason" },
+            "data": { "id": "771825006014889984", "name": "config", "type": 1, "options": options },
+        });
+        InteractionRequest::from_json(&req.to_string()).unwrap()
+    }
+
+    #[test]
+    fn focused_option() {
+        let req = autocomplete_request(json!([{
+            "name": "set",
+            "type": 2,
+            "options": [{
+                "name": "channel",
+                "type": 1,
+                "options": [
+                    { "name": "kind", "type": 3, "value": "text" },
+                    { "name": "name", "type": 3, "value": "gen", "focused": true },
+                ],
+            }],
+        }]));
+        let ac = AutocompleteRequest::from(&req);
+
+        assert_eq!(ac.focused_name(), Some("name"));
+        assert_eq!(
+            ac.focused_option(),
+            Some(("name".to_string(), "gen".to_string()))
+        );
+        assert_eq!(ac.command.option_str("kind"), Some("text"));
+    }
+
+    #[test]
+    fn nothing_focused() {
+        let req = autocomplete_request(json!([{ "name": "kind", "type": 3, "value": "text" }]));
+        let ac = AutocompleteRequest::from(&req);
+        assert_eq!(ac.focused_name(), None);
+        assert_eq!(ac.focused_option(), None);
+
+        // A malformed group without a subcommand has no options to read.
+        let req = autocomplete_request(json!([{ "name": "set", "type": 2, "options": [] }]));
+        let ac = AutocompleteRequest::from(&req);
+        assert_eq!(ac.focused_name(), None);
+        assert!(ac.command.subcommand.is_none());
+    }
+
+    fn command_request(data: serde_json::Value) -> InteractionRequest {
+        let req = json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 2,
+            "guild_id": "290926798626357999",
+            "member": { "user": { "id": "53908232506183680", "username": "mason" } },
+            "data": data,
+        });
+        InteractionRequest::from_json(&req.to_string()).unwrap()
+    }
+
+    #[test]
+    fn command_views() {
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "echo",
+            "type": 1,
+            "options": [{ "name": "text", "type": 3, "value": "hi" }],
+        })));
+        let slash = ac.as_slash_command().unwrap();
+        assert_eq!(slash.name, "echo");
+        assert_eq!(
+            slash.options[0].value,
+            CommandOptionValue::String("hi".into())
+        );
+        assert!(ac.as_user_command().is_none());
+        assert!(ac.as_message_command().is_none());
+
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "Profile",
+            "type": 2,
+            "target_id": "164861519431368704",
+            "resolved": {
+                "users": { "164861519431368704": { "id": "164861519431368704", "username": "juniper" } },
+                "members": { "164861519431368704": { "nick": "June", "roles": [] } },
+            },
+        })));
+        let user = ac.as_user_command().unwrap();
+        assert_eq!(user.user.username, "juniper");
+        assert_eq!(user.member.unwrap().nick.as_deref(), Some("June"));
+        assert!(ac.as_slash_command().is_none());
+        assert!(ac.as_message_command().is_none());
+
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "Quote",
+            "type": 3,
+            "target_id": "111111111111111111",
+            "resolved": {
+                "messages": { "111111111111111111": { "id": "111111111111111111", "content": "hey" } },
+            },
+        })));
+        assert_eq!(ac.as_message_command().unwrap().message.text, "hey");
+        assert!(ac.as_slash_command().is_none());
+        assert!(ac.as_user_command().is_none());
+    }
+
+    #[test]
+    fn subcommands() {
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "config",
+            "type": 1,
+            "options": [{
+                "name": "set",
+                "type": 2,
+                "options": [{
+                    "name": "channel",
+                    "type": 1,
+                    "options": [{ "name": "target", "type": 7, "value": "645027906669510667" }],
+                }],
+            }],
+        })));
+        let sub = ac.subcommand.as_ref().unwrap();
+        assert_eq!(sub.group.as_deref(), Some("set"));
+        assert_eq!(sub.name, "channel");
+        assert_eq!(
+            ac.option_channel("target"),
+            Some(&Snowflake::from("645027906669510667"))
+        );
+
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "config",
+            "type": 1,
+            "options": [{ "name": "reset", "type": 1 }],
+        })));
+        let sub = ac.subcommand.as_ref().unwrap();
+        assert_eq!(sub.group, None);
+        assert_eq!(sub.name, "reset");
+        assert!(ac.options.is_empty());
+
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "echo",
+            "type": 1,
+            "options": [{ "name": "text", "type": 3, "value": "hi" }],
+        })));
+        assert!(ac.subcommand.is_none());
+        assert_eq!(ac.options.len(), 1);
+    }
+
+    #[test]
+    fn typed_options() {
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "poll",
+            "type": 1,
+            "options": [
+                { "name": "question", "type": 3, "value": "Lunch?" },
+                { "name": "count", "type": 4, "value": 3 },
+                { "name": "anonymous", "type": 5, "value": true },
+                { "name": "target", "type": 6, "value": "164861519431368704" },
+                { "name": "ratio", "type": 10, "value": 0.5 },
+            ],
+        })));
+
+        assert_eq!(ac.option_str("question"), Some("Lunch?"));
+        assert_eq!(ac.option_int("count"), Some(3));
+        assert_eq!(ac.option_bool("anonymous"), Some(true));
+        assert_eq!(
+            ac.option_user("target"),
+            Some(&Snowflake::from("164861519431368704"))
+        );
+        assert_eq!(ac.option_number("ratio"), Some(0.5));
+
+        // Accessors of the wrong type, and missing options, give `None`.
+        assert_eq!(ac.option_int("question"), None);
+        assert_eq!(ac.option_role("target"), None);
+        assert_eq!(ac.option_str("missing"), None);
+        assert_eq!(ac.option("count"), Some(&CommandOptionValue::Integer(3)));
+    }
+
+    #[test]
+    fn user_names_and_avatars() {
+        let mut user = User {
+            id: "80351110224678912".into(),
+            username: "nelly".to_string(),
+            global_name: None,
+            discriminator: "0".to_string(),
+            avatar: None,
+            banner: None,
+            accent_color: None,
         };
+        assert_eq!(user.display_name(), "nelly");
+        // Migrated users get one of 6 default avatars, picked by their id.
+        assert_eq!(
+            user.avatar_url(),
+            "https://cdn.discordapp.com/embed/avatars/5.png"
+        );
 
-        discord_types::InteractionResponse {
-            r#type: discord_types::InteractionCallbackType::Modal,
-            data: Some(data),
-        }
+        // Legacy users get one of 5, picked by their discriminator.
+        user.discriminator = "1337".to_string();
+        assert_eq!(
+            user.avatar_url(),
+            "https://cdn.discordapp.com/embed/avatars/2.png"
+        );
+
+        user.global_name = Some("Nelly".to_string());
+        user.avatar = Some("8342729096ea3675442027381ff50dfe".to_string());
+        assert_eq!(user.display_name(), "Nelly");
+        assert_eq!(
+            user.avatar_url(),
+            "https://cdn.discordapp.com/avatars/80351110224678912/8342729096ea3675442027381ff50dfe.png"
+        );
+
+        user.avatar = Some("a_8342729096ea3675442027381ff50dfe".to_string());
+        assert!(user
+            .avatar_url()
+            .ends_with("/a_8342729096ea3675442027381ff50dfe.gif"));
     }
-}
 
-impl Into<discord_types::Component> for &Button {
-    fn into(self) -> discord_types::Component {
-        discord_types::Component {
-            r#type: discord_types::ComponentType::Button,
-            label: Some(self.text.clone()),
-            style: Some(1),
-            custom_id: Some(self.id.clone()),
-            value: None,
-            components: None,
-        }
+    #[test]
+    fn deferred_ephemeral() {
+        let res: discord_types::InteractionResponse =
+            Response::defer_ephemeral().try_into().unwrap();
+        let json = serde_json::to_value(res).unwrap();
+        assert_eq!(json["type"], 5);
+        assert_eq!(json["data"]["flags"], 64);
+
+        let res: discord_types::InteractionResponse =
+            Response::Deferred { ephemeral: false }.try_into().unwrap();
+        assert_eq!(serde_json::to_value(res).unwrap()["data"]["flags"], 0);
+    }
+
+    #[test]
+    fn duplicate_custom_ids_are_an_error() {
+        let msg = Message::new()
+            .row(vec![Button::new("same", "First").into()])
+            .row(vec![Button::new("same", "Second").into()]);
+        let err = discord_types::InteractionResponse::try_from(msg).unwrap_err();
+        assert!(err.to_string().contains("Duplicate component id"));
+    }
+
+    #[test]
+    fn resolved_member() {
+        let options = json!([{ "name": "target", "type": 6, "value": "164861519431368704" }]);
+        let users =
+            json!({ "164861519431368704": { "id": "164861519431368704", "username": "juniper" } });
+
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "roles",
+            "type": 1,
+            "options": options,
+            "resolved": {
+                "users": users,
+                "members": { "164861519431368704": { "nick": "June", "roles": ["41771983423143936"] } },
+            },
+        })));
+        let member = ac.get_resolved_member("target").unwrap();
+        assert_eq!(member.nick.as_deref(), Some("June"));
+        assert_eq!(member.roles, vec![Snowflake::from("41771983423143936")]);
+        assert!(ac.get_resolved_member("missing").is_none());
+
+        // In DMs, only the user is resolved.
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "roles",
+            "type": 1,
+            "options": options,
+            "resolved": { "users": users },
+        })));
+        assert_eq!(
+            ac.option_resolved_user("target").unwrap().username,
+            "juniper"
+        );
+        assert!(ac.get_resolved_member("target").is_none());
+    }
+
+    #[test]
+    fn partial_resolved_roles_and_attachments() {
+        let ac = ApplicationCommand::from(&command_request(json!({
+            "name": "grant",
+            "type": 1,
+            "options": [
+                { "name": "role", "type": 8, "value": "41771983423143936" },
+                { "name": "file", "type": 11, "value": "1100000000000000000" },
+            ],
+            "resolved": {
+                "roles": {
+                    "41771983423143936": {
+                        "id": "41771983423143936",
+                        "name": "Helper",
+                        "permissions": "8",
+                        "tags": { "bot_id": "164861519431368704" },
+                    },
+                },
+                "attachments": {
+                    "1100000000000000000": { "id": "1100000000000000000", "filename": "a.png" },
+                },
+            },
+        })));
+
+        let role = &ac.resolved.roles[&Snowflake::from("41771983423143936")];
+        assert_eq!(role.permissions, 8);
+        assert_eq!(role.icon, None);
+        assert_eq!(role.bot_id, Some(Snowflake::from("164861519431368704")));
+
+        let attachment = ac.option_resolved_attachment("file").unwrap();
+        assert_eq!(attachment.filename, "a.png");
+        assert_eq!(attachment.size, None);
+        assert_eq!(attachment.url, None);
+    }
+
+    #[test]
+    fn file_description_length() {
+        let file = |description: &str| {
+            Message::new().file(FileUpload::new("cat.png", Vec::new()).description(description))
+        };
+
+        let res = discord_types::InteractionResponse::try_from(file(&"a".repeat(1024))).unwrap();
+        assert_eq!(
+            serde_json::to_value(res).unwrap()["data"]["attachments"][0]["description"]
+                .as_str()
+                .unwrap()
+                .len(),
+            1024
+        );
+
+        let err =
+            discord_types::InteractionResponse::try_from(file(&"a".repeat(1025))).unwrap_err();
+        assert!(err.to_string().contains("at most 1024 characters"));
+    }
+
+    #[test]
+    fn special_buttons_keep_their_style() {
+        let link = Button::link("Docs", "https://discord.com").style(ButtonStyle::Danger);
+        assert_eq!(link.style, ButtonStyle::Link);
+
+        let button = Button::new("buy", "Buy").style(ButtonStyle::Premium);
+        assert_eq!(button.style, ButtonStyle::Primary);
+
+        let button = Button::new("delete", "Delete").style(ButtonStyle::Danger);
+        assert_eq!(button.style, ButtonStyle::Danger);
+    }
+
+    #[test]
+    fn modal_retry_state() {
+        let ms = ModalSubmit::from(
+            &InteractionRequest::from_json(include_str!("../tests/fixtures/modal_submit.json"))
+                .unwrap(),
+        );
+        let message = match ms.retry(&["v1 must be a number"]) {
+            Response::Message(message) => message,
+            _ => panic!("Expected a message"),
+        };
+        assert!(message.embeds.is_empty());
+        assert!(!message.text.contains("first"));
+
+        let mut mc = MessageComponent::from(&component_request(None));
+        mc.id = first_button(&message).id.clone();
+        let retry = mc.modal_retry().unwrap();
+        assert_eq!(retry.modal_id, "my_modal");
+        assert_eq!(retry.values["v1"], "first");
+        assert_eq!(retry.values["v2"], "second");
+    }
+
+    #[test]
+    fn long_custom_ids_are_an_error() {
+        let id = "x".repeat(101);
+        let err = discord_types::InteractionResponse::try_from(Message::new().button(&id, "Go"))
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            format!("Component id `{}` must be at most 100 characters!", id)
+        );
+
+        let mut ms = ModalSubmit::from(
+            &InteractionRequest::from_json(include_str!("../tests/fixtures/modal_submit.json"))
+                .unwrap(),
+        );
+        ms.id = "?".repeat(30);
+        let res = discord_types::InteractionResponse::try_from(ms.retry(&[]));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn modal_retry_escapes_and_limits_values() {
+        let mut ms = ModalSubmit::from(
+            &InteractionRequest::from_json(include_str!("../tests/fixtures/modal_submit.json"))
+                .unwrap(),
+        );
+        ms.values.insert("v1".into(), "a&b=c?100%".into());
+        ms.values.insert("v2".into(), "x".repeat(100));
+
+        let message = match ms.retry(&[]) {
+            Response::Message(message) => message,
+            _ => panic!("Expected a message"),
+        };
+        let custom_id = &first_button(&message).id;
+        assert!(custom_id.chars().count() <= 100);
+
+        let mut mc = MessageComponent::from(&component_request(None));
+        mc.id = custom_id.clone();
+        let retry = mc.modal_retry().unwrap();
+        assert_eq!(retry.values["v1"], "a&b=c?100%");
+        assert!(!retry.values.contains_key("v2"));
+    }
+
+    #[test]
+    fn followups_choose_their_own_visibility() {
+        let followup = Followup::new(Message::new().text("Here's your receipt.")).ephemeral();
+        let res = discord_types::InteractionResponse::try_from(followup).unwrap();
+        let payload = json!(res.data);
+        assert_eq!(payload["content"], "Here's your receipt.");
+        assert_eq!(payload["flags"], message_flags::EPHEMERAL);
+
+        let followup = Followup::new(Message::new().text("Claimed!"));
+        let res = discord_types::InteractionResponse::try_from(followup).unwrap();
+        assert_eq!(json!(res.data)["flags"], 0);
+
+        let followup = Followup::new(Message::new().text("Claimed!").edit());
+        assert!(discord_types::InteractionResponse::try_from(followup).is_err());
+    }
+
+    #[cfg(feature = "download")]
+    #[test]
+    fn fetching_users_needs_a_bot_token() {
+        let req =
+            InteractionRequest::from_json(include_str!("../tests/fixtures/slash_command.json"))
+                .unwrap();
+        let ac = ApplicationCommand::from(&req);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let res = runtime.block_on(ac.fetch_user());
+        assert!(matches!(res, Err(ApiError::MissingToken)));
+    }
+
+    #[cfg(feature = "download")]
+    #[test]
+    fn updates_with_followups() {
+        let res =
+            discord_types::InteractionResponse::try_from(Response::update_with_ephemeral_followup(
+                Message::new().text("Claimed by mason"),
+                Message::new().text("You claimed it!"),
+            ))
+            .unwrap();
+        assert_eq!(
+            res.r#type,
+            discord_types::InteractionCallbackType::UpdateMessage
+        );
+        assert_eq!(json!(res.data)["content"], "Claimed by mason");
+
+        let followup = res.followup.unwrap();
+        let payload = json!(followup.data);
+        assert_eq!(payload["content"], "You claimed it!");
+        assert_eq!(payload["flags"], message_flags::EPHEMERAL);
     }
 }
@@ -0,0 +1,114 @@
+/*!
+ * Serving interactions from a [warp](https://docs.rs/warp) service, for self-hosted bots. The
+ * `interactions` filter verifies and handles interactions POSTed to it, replying with the
+ * handler's response:
+ *
+ * ```ignore
+ * let route = warp::path("interactions")
+ *     .and(discord_interaction::warp::interactions(&public_key, MyHandler));
+ * ```
+ *
+ * Requires the `warp` feature.
+ */
+
+use super::auth::{HttpResponseParts, Runner};
+use super::handler::InteractionHandler;
+use ::warp::http::header::CONTENT_TYPE;
+use ::warp::http::{HeaderMap, StatusCode};
+use ::warp::hyper::body::Bytes;
+use ::warp::reject::Rejection;
+use ::warp::{Filter, Reply};
+use std::sync::Arc;
+
+/// A filter that verifies and handles interactions POSTed to it, using the given application
+/// public key and handler. Panics if the key isn't valid.
+pub fn interactions<T>(
+    public_key: &str,
+    handler: T,
+) -> impl Filter<Extract = (HttpResponseParts,), Error = Rejection> + Clone
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    runner_interactions(Runner::with_handler(public_key, handler))
+}
+
+/// A filter that handles interactions POSTed to it with a configured runner, e.g. one with
+/// middleware or application state.
+pub fn runner_interactions<T>(
+    runner: Runner<T>,
+) -> impl Filter<Extract = (HttpResponseParts,), Error = Rejection> + Clone
+where
+    T: InteractionHandler + Send + Sync + 'static,
+{
+    let runner = Arc::new(runner);
+
+    ::warp::post()
+        .and(::warp::header::headers_cloned())
+        .and(::warp::body::bytes())
+        .then(move |headers: HeaderMap, body: Bytes| {
+            let runner = runner.clone();
+            async move { runner.handle_http(&headers, &body).await }
+        })
+}
+
+impl Reply for HttpResponseParts {
+    fn into_response(self) -> ::warp::reply::Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut res = ::warp::reply::Response::new(self.body.into());
+        *res.status_mut() = status;
+        if let Ok(content_type) = self.content_type.parse() {
+            res.headers_mut().insert(CONTENT_TYPE, content_type);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ApplicationCommand, HandlerError, Message, Response};
+    use serde_json::json;
+
+    struct TestHandler;
+
+    impl InteractionHandler for TestHandler {
+        fn handle_application_command(
+            &self,
+            _ac: ApplicationCommand,
+        ) -> Result<Response, HandlerError> {
+            Ok(Response::Message(Message::new().text("Hello!")))
+        }
+    }
+
+    #[test]
+    fn runner_configuration_applies() {
+        let body = json!({
+            "id": "787654321987654321",
+            "application_id": "123456789012345678",
+            "token": "token",
+            "type": 2,
+            "user": { "id": "53908232506183680", "username": "mason" },
+            "data": { "id": "771825006014889984", "name": "hello", "type": 1 },
+        });
+        let runner = Runner::with_handler(
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            TestHandler,
+        )
+        .insecure_local_mode();
+        let filter = runner_interactions(runner);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let res = runtime
+            .block_on(
+                ::warp::test::request()
+                    .method("POST")
+                    .body(body.to_string())
+                    .filter(&filter),
+            )
+            .unwrap();
+        assert_eq!(res.status, 200);
+        assert!(String::from_utf8(res.body).unwrap().contains("Hello!"));
+    }
+}
@@ -0,0 +1,38 @@
+/*!
+ * Serving interactions from [Cloudflare Workers](https://workers.cloudflare.com), with the
+ * [worker](https://docs.rs/worker) crate. Build for `wasm32-unknown-unknown` without the default
+ * `lambda` feature, and pass each request to `handle`:
+ *
+ * ```ignore
+ * #[event(fetch)]
+ * async fn fetch(req: Request, env: Env, _ctx: Context) -> Result<Response> {
+ *     let public_key = env.var("DISCORD_PUBLIC_KEY")?.to_string();
+ *     discord_interaction::workers::handle(req, &public_key, &MyHandler).await
+ * }
+ * ```
+ *
+ * Handlers are run with `handle_interaction`, so a `Runner`'s configuration doesn't apply.
+ *
+ * Requires the `workers` feature.
+ */
+
+use super::auth::handle_interaction;
+use super::handler::InteractionHandler;
+use worker::{Headers, Request, Response, Result};
+
+/// Verifies and handles an interaction request, returning the response to send back.
+pub async fn handle<T>(mut req: Request, public_key: &str, handler: &T) -> Result<Response>
+where
+    T: InteractionHandler,
+{
+    let headers: Vec<(String, String)> = req.headers().entries().collect();
+    let body = req.bytes().await?;
+
+    let res = handle_interaction(public_key, &headers[..], &body, handler);
+
+    let mut headers = Headers::new();
+    headers.set("Content-Type", &res.content_type)?;
+    Ok(Response::from_bytes(res.body)?
+        .with_status(res.status)
+        .with_headers(headers))
+}
@@ -0,0 +1,81 @@
+//! Parses captured interaction payloads with `InteractionRequest::from_json`, and reads them as
+//! the handler payloads they're dispatched as.
+
+use discord_interaction::{
+    ApplicationCommand, CommandType, InteractionContext, InteractionRequest, MessageComponent,
+    ModalSubmit,
+};
+
+fn fixture_json(name: &str) -> String {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read_to_string(&path).unwrap()
+}
+
+fn fixture(name: &str) -> InteractionRequest {
+    InteractionRequest::from_json(&fixture_json(name)).unwrap()
+}
+
+#[test]
+fn ping() {
+    let req = fixture("ping");
+    assert!(req.data.is_none());
+    assert!(req.invoking_user().is_none());
+    assert!(req.raw_data.is_null());
+}
+
+#[test]
+fn slash_command() {
+    let req = fixture("slash_command");
+    assert_eq!(req.raw_data["options"][1]["value"], 30);
+
+    let ac = ApplicationCommand::from(&req);
+    assert_eq!(ac.command_name, "remind");
+    assert_eq!(ac.command_type, CommandType::ChatInput);
+    assert_eq!(ac.user_id, "53908232506183680");
+    assert_eq!(ac.guild_id.as_ref().unwrap(), "1157377829389439016");
+    assert_eq!(ac.context, Some(InteractionContext::Guild));
+    assert_eq!(ac.option_str("message"), Some("Water the plants"));
+    assert_eq!(ac.option_int("minutes"), Some(30));
+    assert_eq!(
+        ac.option_resolved_user("who").unwrap().display_name(),
+        "Juniper"
+    );
+}
+
+#[test]
+fn component() {
+    let mc = MessageComponent::from(&fixture("component"));
+    assert_eq!(mc.id, "the_button");
+    assert!(mc.values.is_empty());
+    assert_eq!(mc.guild_id().unwrap(), "1157377829389439016");
+    assert_eq!(mc.source.text, "You've clicked the button 3 times.");
+    assert_eq!(mc.source.buttons.len(), 2);
+}
+
+#[test]
+fn modal_submit() {
+    let ms = ModalSubmit::from(&fixture("modal_submit"));
+    assert_eq!(ms.id, "my_modal");
+    assert_eq!(ms.guild_id, None);
+    assert_eq!(ms.field::<String>("v1").unwrap(), "first");
+    assert_eq!(ms.field::<String>("v2").unwrap(), "second");
+    assert_eq!(ms.source.unwrap().text, "Hello <@53908232506183680>!");
+}
+
+#[test]
+fn unknown_component_type() {
+    let mut json: serde_json::Value = serde_json::from_str(&fixture_json("component")).unwrap();
+    json["message"]["components"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!({ "type": 99, "id": 4 }));
+
+    let req = InteractionRequest::from_json(&json.to_string()).unwrap();
+    let mc = MessageComponent::from(&req);
+    assert_eq!(mc.id, "the_button");
+    assert_eq!(mc.source.buttons.len(), 2);
+}